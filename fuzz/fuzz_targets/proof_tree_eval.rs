@@ -0,0 +1,64 @@
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+
+use augmentation::proof_tree::ProofNode;
+
+/// Shape of a random proof tree
+#[derive(Arbitrary, Debug)]
+enum Tree {
+    Leaf(bool),
+    LeafSuccess(bool),
+    Info(Box<Tree>),
+    All(Vec<Tree>),
+    Any(Vec<Tree>),
+    Or(Box<Tree>, Box<Tree>),
+    And(Box<Tree>, Box<Tree>),
+}
+
+impl Tree {
+    fn to_proof_node(&self) -> ProofNode {
+        match self {
+            Tree::Leaf(success) => ProofNode::new_leaf("leaf".into(), *success),
+            Tree::LeafSuccess(tight) => ProofNode::new_leaf_success("leaf".into(), *tight),
+            Tree::Info(child) => ProofNode::new_info("info".into(), child.to_proof_node()),
+            Tree::All(childs) => {
+                let mut node = ProofNode::new_all("all".into());
+                for child in childs {
+                    node.add_child(child.to_proof_node());
+                }
+                node
+            }
+            Tree::Any(childs) => {
+                let mut node = ProofNode::new_any("any".into());
+                for child in childs {
+                    node.add_child(child.to_proof_node());
+                }
+                node
+            }
+            Tree::Or(child1, child2) => {
+                ProofNode::new_or(child1.to_proof_node(), child2.to_proof_node())
+            }
+            Tree::And(child1, child2) => {
+                ProofNode::new_and(child1.to_proof_node(), child2.to_proof_node())
+            }
+        }
+    }
+}
+
+fuzz_target!(|tree: Tree| {
+    let mut proof = tree.to_proof_node();
+    let mut pruned = proof.clone();
+
+    // eval caches the outcome, so a second call must not change it
+    let first = proof.eval();
+    proof.assert_complete();
+    let second = proof.eval();
+    assert_eq!(first, second);
+    assert_eq!(first, proof.outcome());
+
+    // pruning must not change the outcome either
+    assert_eq!(first, pruned.eval_and_prune());
+    assert_eq!(first, pruned.eval());
+});
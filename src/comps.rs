@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 use itertools::Itertools;
 
-use crate::{Credit, CreditInv, Graph, Node};
+use crate::{util::hamiltonian_paths, Credit, CreditInv, Graph, Node};
 
 use super::types::Edge;
 
@@ -16,25 +16,21 @@ pub enum EdgeType {
     Buyable,
 }
 
+#[cfg(test)]
+pub fn c3() -> Component {
+    Component::C3(Node::node_range(3).try_into().unwrap())
+}
 pub fn c4() -> Component {
-    Component::C4([0.into(), 1.into(), 2.into(), 3.into()])
+    Component::C4(Node::node_range(4).try_into().unwrap())
 }
 pub fn c5() -> Component {
-    Component::C5([0.into(), 1.into(), 2.into(), 3.into(), 4.into()])
+    Component::C5(Node::node_range(5).try_into().unwrap())
 }
 pub fn c6() -> Component {
-    Component::C6([0.into(), 1.into(), 2.into(), 3.into(), 4.into(), 5.into()])
+    Component::C6(Node::node_range(6).try_into().unwrap())
 }
 pub fn c7() -> Component {
-    Component::C7([
-        0.into(),
-        1.into(),
-        2.into(),
-        3.into(),
-        4.into(),
-        5.into(),
-        6.into(),
-    ])
+    Component::C7(Node::node_range(7).try_into().unwrap())
 }
 pub fn large() -> Component {
     Component::Large(Node::Comp(0))
@@ -65,26 +61,108 @@ pub enum Component {
     Large(Node),
 }
 
+/// Lower bound on the number of vertices (and edges) of a large component.
+const LARGE_MIN_SIZE: u32 = 8;
+
 impl Component {
+    /// Returns the node which represents a large component, and `None` for all other components.
+    pub fn large_node_id(&self) -> Option<Node> {
+        match self {
+            Component::Large(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of vertices we assume for a large component, and `None` for all other components.
+    /// This is not the id of the representative node, see `large_node_id`.
+    pub fn large_count(&self) -> Option<u32> {
+        match self {
+            Component::Large(_) => Some(LARGE_MIN_SIZE),
+            _ => None,
+        }
+    }
+
+    /// Creates a component of type `comp_type` from `graph`. For cycles, `graph` must be a simple cycle
+    /// of the given length; the cyclic order of the nodes is recovered from the edges, starting at the
+    /// smallest node. As for all cycle components, the nodes must be numbered sequentially along the cycle.
+    /// A large component is represented by the smallest node of `graph`.
+    #[cfg(test)]
+    pub fn from_petgraph(graph: Graph, comp_type: CompType) -> Component {
+        let start = graph
+            .nodes()
+            .min()
+            .expect("cannot create component from empty graph");
+        let len = match comp_type {
+            CompType::Large => return Component::Large(start),
+            CompType::Cycle(len) => len,
+        };
+
+        assert_eq!(graph.node_count(), len, "graph is not a C{}", len);
+        assert_eq!(graph.edge_count(), len, "graph is not a C{}", len);
+        assert!(graph.nodes().all(|v| graph.neighbors(v).count() == 2));
+
+        let mut nodes = vec![start];
+        while nodes.len() < len {
+            let current = *nodes.last().unwrap();
+            let next = graph
+                .neighbors(current)
+                .filter(|v| !nodes.contains(v))
+                .min()
+                .unwrap_or_else(|| panic!("graph is not a C{}", len));
+            nodes.push(next);
+        }
+        assert!(graph.contains_edge(*nodes.last().unwrap(), start));
+        debug_assert!(nodes
+            .windows(2)
+            .all(|w| w[1].to_vertex() == w[0].to_vertex() + 1));
+
+        match len {
+            3 => Component::C3(nodes.try_into().unwrap()),
+            4 => Component::C4(nodes.try_into().unwrap()),
+            5 => Component::C5(nodes.try_into().unwrap()),
+            6 => Component::C6(nodes.try_into().unwrap()),
+            7 => Component::C7(nodes.try_into().unwrap()),
+            _ => panic!("Cycles of length {} are not supported!", len),
+        }
+    }
+
     pub fn is_cycle(&self) -> bool {
         self.is_c4() || self.is_c5() || self.is_c6() || self.is_c7()
     }
 
+    /// Returns true if this component is a cycle with `k` nodes.
+    pub fn is_cycle_of_size(&self, k: u32) -> bool {
+        matches!(self.comp_type(), CompType::Cycle(n) if n == k as usize)
+    }
+
+    #[inline]
     pub fn is_c7(&self) -> bool {
-        matches!(self, Component::C7(_))
+        self.is_cycle_of_size(7)
     }
+
+    #[inline]
     pub fn is_c6(&self) -> bool {
-        matches!(self, Component::C6(_))
+        self.is_cycle_of_size(6)
     }
 
+    #[inline]
     pub fn is_c5(&self) -> bool {
-        matches!(self, Component::C5(_))
+        self.is_cycle_of_size(5)
     }
 
+    #[inline]
     pub fn is_c4(&self) -> bool {
-        matches!(self, Component::C4(_))
+        self.is_cycle_of_size(4)
+    }
+
+    #[inline]
+    pub fn is_c3(&self) -> bool {
+        self.is_cycle_of_size(3)
     }
 
+    /// Returns the two arc-disjoint paths in the cycle from `v` to `u`. The first element is the
+    /// clockwise arc (following the order of `nodes()`), the second is the counterclockwise arc
+    /// (from `u` further along `nodes()` back to `v`). Both paths contain `v` and `u`.
     pub fn paths_between(&self, v: &Node, u: &Node) -> (Vec<Node>, Vec<Node>) {
         let nodes = self.nodes().to_owned();
 
@@ -111,6 +189,19 @@ impl Component {
         (path1, path2)
     }
 
+    /// Returns the number of edges on the clockwise and on the counterclockwise arc from `from` to `to`, where
+    /// clockwise follows the order of `nodes()`.
+    pub fn cycle_distance(&self, from: Node, to: Node) -> (usize, usize) {
+        let nodes = self.nodes();
+        let n = nodes.len();
+        let pos_from = nodes.iter().position(|v| *v == from).unwrap();
+        let pos_to = nodes.iter().position(|v| *v == to).unwrap();
+
+        let clockwise = (pos_to + n - pos_from) % n;
+        let counterclockwise = (n - clockwise) % n;
+        (clockwise, counterclockwise)
+    }
+
     pub fn symmetric_combs(&self) -> Vec<[Node; 2]> {
         match self {
             // must be consistent with the fact that fixed node is n[0]!!! see below
@@ -134,6 +225,19 @@ impl Component {
         }
     }
 
+    /// Returns true if every tactic has explicit handling for this type of component. Tactics may
+    /// panic or give wrong results for other components.
+    pub fn is_supported_by_all_tactics(&self) -> bool {
+        match self {
+            Component::C7(_)
+            | Component::C6(_)
+            | Component::C5(_)
+            | Component::C4(_)
+            | Component::Large(_) => true,
+            Component::C3(_) => false,
+        }
+    }
+
     pub fn is_large(&self) -> bool {
         matches!(self, Component::Large(_))
     }
@@ -150,8 +254,28 @@ impl Component {
         }
     }
 
-    // /// A list of all nodes which could be in-nodes.
-    // /// Removes symmetric cases.
+    /// Returns all nodes of this component which are not contained in `nodes`.
+    pub fn complement_nodes(&self, nodes: &[Node]) -> Vec<Node> {
+        self.nodes()
+            .iter()
+            .filter(|n| !nodes.contains(n))
+            .cloned()
+            .collect_vec()
+    }
+
+    /// Returns all nodes of this component which are adjacent to both `u` and `v`.
+    pub fn vertices_adjacent_to_both(&self, u: Node, v: Node) -> Vec<Node> {
+        self.nodes()
+            .iter()
+            .filter(|w| self.is_adjacent(w, &u) && self.is_adjacent(w, &v))
+            .cloned()
+            .collect_vec()
+    }
+
+    /// Returns the nodes which have to be considered as in-node of this component in a nice path, given that the
+    /// out-node is `fixed_node()`. Any node can be the in-node, but a cycle is symmetric with respect to the
+    /// reflection fixing `fixed_node()`. Hence, it suffices to consider the nodes with distance at most `n / 2` to
+    /// the fixed node along the cycle. In contrast to `nodes()`, this removes the symmetric cases.
     pub fn in_nodes(&self) -> &[Node] {
         match self {
             Component::Large(n) => std::slice::from_ref(n),
@@ -160,9 +284,9 @@ impl Component {
     }
 
     pub fn incident(&self, edge: &Edge) -> Option<Node> {
-        if let Component::Large(n) = self {
-            if edge.node_incident(n) {
-                Some(*n)
+        if let Some(n) = self.large_node_id() {
+            if edge.node_incident(&n) {
+                Some(n)
             } else {
                 None
             }
@@ -182,6 +306,8 @@ impl Component {
         }
     }
 
+    /// Returns the name of the component type. The name is used in proof file names, so it must be
+    /// unique per variant and filesystem-safe.
     pub fn short_name(&self) -> String {
         match self {
             Component::C7(_) => "C7".to_string(),
@@ -200,7 +326,7 @@ impl Component {
             Component::C5(_) => 5,
             Component::C4(_) => 4,
             Component::C3(_) => 3,
-            Component::Large(_) => 8,
+            Component::Large(_) => self.large_count().unwrap() as usize,
         }
     }
 
@@ -211,7 +337,7 @@ impl Component {
             Component::C5(_) => 5,
             Component::C4(_) => 4,
             Component::C3(_) => 3,
-            Component::Large(_) => 8,
+            Component::Large(_) => self.large_count().unwrap() as usize,
         }
     }
 
@@ -228,13 +354,50 @@ impl Component {
         }
     }
 
-    pub fn white_nodes(&self) -> Vec<Node> {
-        match self {
-            Component::Large(n) => vec![*n],
-            _ => vec![],
+    /// Returns true if there is a Hamiltonian path from `u` to `v` using only edges of this component, i.e., if `(u, v)` can be a nice pair.
+    /// Large components are abstract, so every pair is considered nice.
+    pub fn is_path_nice_pair(&self, u: Node, v: Node) -> bool {
+        if self.is_large() {
+            return true;
+        }
+        hamiltonian_paths(u, v, self.nodes())
+            .iter()
+            .any(|path| path.windows(2).all(|w| self.is_adjacent(&w[0], &w[1])))
+    }
+
+    /// Returns true if every node of `graph()` has exactly `k` neighbors.
+    pub fn is_regular(&self, k: u32) -> bool {
+        let graph = self.graph();
+        graph
+            .nodes()
+            .all(|n| graph.neighbors(n).count() == k as usize)
+    }
+
+    /// Returns true if `graph()` contains an edge which is not an edge of the cycle.
+    #[cfg(test)]
+    pub fn has_chord(&self) -> bool {
+        self.graph()
+            .all_edges()
+            .any(|(u, v, _)| !self.is_adjacent(&u, &v))
+    }
+
+    /// Returns the component obtained by adding the edge `(u, v)`. If the edge is already present,
+    /// the component is unchanged. Otherwise the result is no longer a cycle, so we can only
+    /// represent it as a large component, whose node takes the label of the fixed node.
+    #[cfg(test)]
+    pub fn add_chord(&self, u: Node, v: Node) -> Component {
+        assert!(self.contains(&u) && self.contains(&v));
+        if u == v || self.is_adjacent(&u, &v) || self.is_large() {
+            self.clone()
+        } else {
+            Component::Large(Node::Comp(self.fixed_node().get_id()))
         }
     }
 
+    pub fn white_nodes(&self) -> Vec<Node> {
+        self.large_node_id().into_iter().collect()
+    }
+
     pub fn graph(&self) -> Graph {
         match self {
             Component::C7(_)
@@ -255,6 +418,22 @@ impl Component {
         }
     }
 
+    /// Returns true if `graph()` has a Hamiltonian cycle, which holds for every cycle component.
+    /// Large components are abstract and always return true.
+    pub fn has_hamiltonian_cycle(&self) -> bool {
+        self.is_large() || graph_has_hamiltonian_cycle(&self.graph())
+    }
+
+    /// Returns all edges of `graph()` which have type `typ`.
+    #[cfg(test)]
+    pub fn edges_of_type(&self, typ: EdgeType) -> Vec<(Node, Node)> {
+        self.graph()
+            .all_edges()
+            .filter(|(_, _, t)| **t == typ)
+            .map(|(a, b, _)| (a, b))
+            .collect()
+    }
+
     pub fn comp_type(&self) -> CompType {
         match self {
             Component::C7(nodes) => CompType::Cycle(nodes.len()),
@@ -266,6 +445,13 @@ impl Component {
         }
     }
 
+    /// Returns the size of a maximum matching in the graph of this component. For large components
+    /// only the representative node is known, so this is 0.
+    #[cfg(test)]
+    pub fn max_matching_size(&self) -> usize {
+        petgraph::algo::maximum_matching(&self.graph()).len()
+    }
+
     pub fn combinations(&self, size: usize) -> Vec<Vec<Node>> {
         match self {
             Component::Large(n) => vec![vec![*n; size]],
@@ -278,6 +464,8 @@ impl Component {
         }
     }
 
+    /// Returns all multisets of `size` nodes of this component. A large component is represented by a single node,
+    /// so there is only the multiset which contains this node `size` times.
     pub fn combinations_with_replacement(&self, size: usize) -> Vec<Vec<Node>> {
         match self {
             Component::Large(n) => vec![vec![*n; size]],
@@ -291,13 +479,56 @@ impl Component {
     }
 
     pub fn contains(&self, node: &Node) -> bool {
-        if let Component::Large(n) = self {
-            n == node
+        if let Some(n) = self.large_node_id() {
+            n == *node
         } else {
             self.nodes().contains(node)
         }
     }
 
+    /// A canonical string of the component graph which is invariant under relabeling of the nodes.
+    /// It is the lexicographically smallest adjacency matrix (row by row) over all node orderings.
+    pub fn to_isomorphism_class(&self) -> String {
+        if let Component::Large(_) = self {
+            return self.short_name();
+        }
+
+        let nodes = self.nodes();
+        nodes
+            .iter()
+            .permutations(nodes.len())
+            .map(|order| {
+                order
+                    .iter()
+                    .map(|u| {
+                        order
+                            .iter()
+                            .map(|v| if self.is_adjacent(u, v) { '1' } else { '0' })
+                            .collect::<String>()
+                    })
+                    .join("|")
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Returns true if the node ids of this component are exactly `0..num_labels()`, which is the labeling
+    /// `relabels_nodes_sequentially` relies on. In particular, all nodes of a cycle are distinct vertices.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Component::Large(n) => *n == Node::Comp(0),
+            _ => {
+                self.nodes().iter().all(|n| !n.is_comp())
+                    && self
+                        .nodes()
+                        .iter()
+                        .map(|n| n.get_id())
+                        .sorted()
+                        .eq(0..self.num_labels() as u32)
+            }
+        }
+    }
+
     pub fn num_labels(&self) -> usize {
         match self {
             Component::C7(_) => 7,
@@ -310,6 +541,33 @@ impl Component {
     }
 }
 
+/// Searches a Hamiltonian cycle in `graph` by backtracking over all paths starting at an arbitrary node.
+fn graph_has_hamiltonian_cycle(graph: &Graph) -> bool {
+    let nodes = graph.nodes().collect_vec();
+    if nodes.len() < 3 {
+        return false;
+    }
+    let mut path = vec![nodes[0]];
+    extend_to_hamiltonian_cycle(graph, &mut path, nodes.len())
+}
+
+fn extend_to_hamiltonian_cycle(graph: &Graph, path: &mut Vec<Node>, num_nodes: usize) -> bool {
+    let last = *path.last().unwrap();
+    if path.len() == num_nodes {
+        return graph.contains_edge(last, path[0]);
+    }
+    for next in graph.neighbors(last).collect_vec() {
+        if !path.contains(&next) {
+            path.push(next);
+            if extend_to_hamiltonian_cycle(graph, path, num_nodes) {
+                return true;
+            }
+            path.pop();
+        }
+    }
+    false
+}
+
 fn is_adjacent_in_cycle(nodes: &[Node], v1: &Node, v2: &Node) -> bool {
     if !nodes.contains(v1) || !nodes.contains(v2) || v1.is_comp() || v2.is_comp() {
         return false;
@@ -349,14 +607,6 @@ impl Display for Component {
 //     pub num_blocks: usize,
 // }
 
-// pub fn edges_of_type(graph: &Graph, typ: EdgeType) -> Vec<(Node, Node)> {
-//     graph
-//         .all_edges()
-//         .filter(|(_, _, t)| **t == typ)
-//         .map(|(a, b, _)| (a, b))
-//         .collect()
-// }
-
 impl CreditInv {
     pub fn credits(&self, comp: &Component) -> Credit {
         match comp {
@@ -369,3 +619,196 @@ impl CreditInv {
         }
     }
 }
+
+#[cfg(test)]
+mod test_comps {
+    use super::*;
+    use crate::util::relabels_nodes_sequentially;
+
+    #[test]
+    fn test_has_hamiltonian_cycle() {
+        for comp in [c4(), c5(), c6(), c7()] {
+            assert!(comp.has_hamiltonian_cycle());
+        }
+
+        let path = Graph::from_edges(
+            (0..3)
+                .map(|i| (Node::n(i), Node::n(i + 1), EdgeType::Sellable))
+                .collect_vec(),
+        );
+        assert!(!graph_has_hamiltonian_cycle(&path));
+    }
+
+    #[test]
+    fn test_combinations_with_replacement_c5() {
+        let comp = c5();
+        let pairs = comp.combinations_with_replacement(2);
+        assert_eq!(pairs.len(), 15);
+        assert!(pairs.iter().all_unique());
+        assert!(pairs.contains(&vec![Node::n(1), Node::n(1)]));
+
+        // multisets of size 3 from 5 nodes: binom(7, 3)
+        let triples = comp.combinations_with_replacement(3);
+        assert_eq!(triples.len(), 35);
+        assert!(triples.iter().all_unique());
+
+        assert_eq!(
+            large().combinations_with_replacement(3),
+            vec![vec![large().fixed_node(); 3]]
+        );
+    }
+
+    #[test]
+    fn test_is_valid() {
+        for comp in [c3(), c4(), c5(), c6(), c7(), large()] {
+            assert!(comp.is_valid());
+        }
+
+        let mut comps = vec![c4(), c5()];
+        relabels_nodes_sequentially(&mut comps, 0);
+        assert!(comps[0].is_valid());
+        assert!(!comps[1].is_valid());
+
+        assert!(!Component::C4([Node::n(0), Node::n(1), Node::n(1), Node::n(2)]).is_valid());
+        assert!(!Component::Large(Node::n(0)).is_valid());
+    }
+
+    #[test]
+    fn test_add_chord() {
+        for comp in [c4(), c5(), c6(), c7()] {
+            let chord = comp.add_chord(Node::n(0), Node::n(2));
+            assert!(chord.is_large());
+            assert!(chord.is_valid());
+
+            // edges of the cycle are not chords
+            assert_eq!(comp.add_chord(Node::n(0), Node::n(1)).nodes(), comp.nodes());
+        }
+        assert!(large().add_chord(Node::c(0), Node::c(0)).is_valid());
+    }
+
+    #[test]
+    fn test_edges_of_type() {
+        for comp in [c3(), c4(), c5(), c6(), c7()] {
+            // all cycle edges are sellable
+            assert_eq!(
+                comp.edges_of_type(EdgeType::Sellable).len(),
+                comp.edges().len()
+            );
+            assert!(comp.edges_of_type(EdgeType::Fixed).is_empty());
+            assert!(comp.edges_of_type(EdgeType::Buyable).is_empty());
+        }
+        assert!(large().edges_of_type(EdgeType::Sellable).is_empty());
+    }
+
+    #[test]
+    fn test_has_chord() {
+        // the graph of a cycle component consists of the cycle edges only
+        for comp in [c3(), c4(), c5(), c6(), c7(), large()] {
+            assert!(!comp.has_chord());
+        }
+    }
+
+    #[test]
+    fn test_in_nodes_c5() {
+        let comp = c5();
+        let in_nodes = comp.in_nodes();
+        assert_eq!(in_nodes, &[Node::n(0), Node::n(1), Node::n(2)]);
+        assert!(in_nodes.iter().all(|n| comp.nodes().contains(n)));
+
+        // every node is an in-node or the reflection of one
+        let nodes = comp.nodes();
+        for (i, node) in nodes.iter().enumerate() {
+            let mirrored = nodes[(nodes.len() - i) % nodes.len()];
+            assert!(in_nodes.contains(node) || in_nodes.contains(&mirrored));
+        }
+    }
+
+    #[test]
+    fn test_is_path_nice_pair_c5() {
+        let comp = c5();
+        for (u, v) in comp.nodes().iter().tuple_combinations() {
+            assert_eq!(comp.is_path_nice_pair(*u, *v), comp.is_adjacent(u, v));
+        }
+    }
+
+    #[test]
+    fn test_paths_between_c5() {
+        let comp = c5();
+        let v = Node::n(0);
+        let u = Node::n(2);
+        let (upper, lower) = comp.paths_between(&v, &u);
+
+        assert_eq!(upper, vec![0.into(), 1.into(), 2.into()]);
+        assert_eq!(lower, vec![2.into(), 3.into(), 4.into(), 0.into()]);
+
+        // the inner nodes of both arcs partition all nodes except v and u
+        let mut inner = upper[1..upper.len() - 1]
+            .iter()
+            .chain(lower[1..lower.len() - 1].iter())
+            .cloned()
+            .collect_vec();
+        inner.sort();
+        let expected = comp
+            .nodes()
+            .iter()
+            .filter(|n| **n != v && **n != u)
+            .cloned()
+            .collect_vec();
+        assert_eq!(inner, expected);
+    }
+
+    #[test]
+    fn test_from_petgraph() {
+        for comp in [c3(), c4(), c5(), c6(), c7()] {
+            let from_graph = Component::from_petgraph(comp.graph(), comp.comp_type());
+            assert_eq!(from_graph.nodes(), comp.nodes());
+        }
+
+        // the cyclic order is recovered from the edges, not from the insertion order
+        let graph = Graph::from_edges([
+            (Node::n(7), Node::n(4), EdgeType::Sellable),
+            (Node::n(5), Node::n(6), EdgeType::Sellable),
+            (Node::n(6), Node::n(7), EdgeType::Sellable),
+            (Node::n(4), Node::n(5), EdgeType::Sellable),
+        ]);
+        let comp = Component::from_petgraph(graph, CompType::Cycle(4));
+        assert!(comp.is_c4());
+        assert_eq!(comp.nodes(), &[4.into(), 5.into(), 6.into(), 7.into()]);
+        assert!(comp.is_adjacent(&Node::n(4), &Node::n(7)));
+        assert!(!comp.is_adjacent(&Node::n(4), &Node::n(6)));
+
+        let comp = Component::from_petgraph(large().graph(), CompType::Large);
+        assert!(comp.is_large());
+    }
+
+    #[test]
+    fn test_short_name() {
+        let names = [c3(), c4(), c5(), c6(), c7(), large()]
+            .iter()
+            .map(|c| c.short_name())
+            .collect_vec();
+        assert_eq!(names, vec!["C3", "C4", "C5", "C6", "C7", "Large"]);
+        assert!(names.iter().all_unique());
+        assert!(names
+            .iter()
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_alphanumeric())));
+    }
+
+    #[test]
+    fn test_max_matching_size() {
+        for comp in [c3(), c4(), c5(), c6(), c7()] {
+            // size of the largest set of pairwise disjoint edges
+            let brute_force = comp
+                .edges()
+                .into_iter()
+                .powerset()
+                .filter(|edges| edges.iter().flat_map(|(u, v)| [*u, *v]).all_unique())
+                .map(|edges| edges.len())
+                .max()
+                .unwrap();
+            assert_eq!(comp.max_matching_size(), brute_force);
+            assert_eq!(comp.max_matching_size(), comp.num_vertices() / 2);
+        }
+        assert_eq!(large().max_matching_size(), 0);
+    }
+}
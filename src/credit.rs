@@ -25,6 +25,14 @@ impl Credit {
     pub fn new(numer: i64, denom: i64) -> Self {
         Credit(Rational64::new(numer, denom))
     }
+
+    /// Like `new`, but returns an error instead of panicking if `denom` is zero, e.g. for user input.
+    pub fn try_new(numer: i64, denom: i64) -> anyhow::Result<Self> {
+        if denom == 0 {
+            anyhow::bail!("Denominator of {}/{} must not be zero", numer, denom);
+        }
+        Ok(Credit::new(numer, denom))
+    }
 }
 
 impl Add for Credit {
@@ -158,6 +166,26 @@ impl CreditInv {
     pub fn new(c: Credit) -> Self {
         CreditInv { c }
     }
+
+    /// Returns `steps` invariants with evenly-spaced values of `c` from `start` to `end` (both inclusive).
+    /// If `steps` is one, only `start` is returned. Fails if `steps` is zero.
+    pub fn for_range(start: Credit, end: Credit, steps: usize) -> anyhow::Result<Vec<CreditInv>> {
+        match steps {
+            0 => anyhow::bail!("The number of steps must be at least 1"),
+            1 => Ok(vec![CreditInv::new(start)]),
+            _ => {
+                let step = (end - start) / Credit::from_integer(steps as i64 - 1);
+                Ok((0..steps)
+                    .map(|i| CreditInv::new(start + step * Credit::from_integer(i as i64)))
+                    .collect())
+            }
+        }
+    }
+
+    /// Returns `c` as a filesystem-safe string, e.g. `2_7` for `c = 2/7`.
+    pub fn to_filename_suffix(&self) -> String {
+        self.c.to_string().replace('/', "_")
+    }
 }
 
 impl CreditInv {
@@ -188,3 +216,53 @@ impl Display for CreditInv {
         write!(f, "Credit Scheme with c = {}", self.c)
     }
 }
+
+#[cfg(test)]
+mod test_credit_inv {
+    use super::*;
+
+    #[test]
+    fn test_to_filename_suffix() {
+        assert_eq!(
+            CreditInv::new(Credit::new(2, 7)).to_filename_suffix(),
+            "2_7"
+        );
+        assert_eq!(CreditInv::new(Credit::new(4, 2)).to_filename_suffix(), "2");
+    }
+
+    fn range_values(start: Credit, end: Credit, steps: usize) -> Vec<Credit> {
+        CreditInv::for_range(start, end, steps)
+            .unwrap()
+            .iter()
+            .map(|inv| inv.c)
+            .collect()
+    }
+
+    #[test]
+    fn test_for_range() {
+        assert_eq!(
+            range_values(Credit::new(1, 5), Credit::new(1, 3), 3),
+            vec![Credit::new(1, 5), Credit::new(4, 15), Credit::new(1, 3)]
+        );
+
+        // a single step only proves the start value
+        assert_eq!(
+            range_values(Credit::new(1, 5), Credit::new(1, 3), 1),
+            vec![Credit::new(1, 5)]
+        );
+
+        // if start and end coincide, every step has the same value
+        assert_eq!(
+            range_values(Credit::new(2, 7), Credit::new(2, 7), 3),
+            vec![Credit::new(2, 7); 3]
+        );
+
+        assert!(CreditInv::for_range(Credit::new(1, 5), Credit::new(1, 3), 0).is_err());
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Credit::try_new(4, 14).unwrap(), Credit::new(2, 7));
+        assert!(Credit::try_new(1, 0).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+//! Library part of the crate. It contains the self-contained modules that are also used outside of
+//! the prover binary, e.g., by the fuzz targets in `fuzz/`.
+
+pub mod proof_tree;
@@ -1,7 +1,7 @@
 use itertools::Itertools;
 use std::fmt::Debug;
 
-use crate::proof_tree::ProofNode;
+use crate::proof_tree::{NodeLimit, ProofNode};
 
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
@@ -13,20 +13,23 @@ pub trait InstanceTrait: Clone + Send + Sync {
 
     fn push(&mut self, item: Self::StackElement);
     fn pop(&mut self);
+
+    /// The limit on the size of proof trees, if any.
+    fn node_limit(&self) -> Option<&NodeLimit> {
+        None
+    }
 }
 
+/// Boxed iterator over the stack elements of an instance of type `I`.
+pub type StackElementIter<I> = Box<dyn Iterator<Item = <I as InstanceTrait>::StackElement>>;
+
 pub trait OptEnumeratorTrait: Clone + Send + Sync {
     type Inst: InstanceTrait;
 
     fn msg(&self) -> &str;
 
-    fn try_iter(
-        &self,
-        instance: &mut Self::Inst,
-    ) -> Option<(
-        Box<dyn Iterator<Item = <Self::Inst as InstanceTrait>::StackElement>>,
-        String,
-    )>;
+    fn try_iter(&self, instance: &mut Self::Inst)
+        -> Option<(StackElementIter<Self::Inst>, String)>;
 }
 
 pub trait EnumeratorTrait: Clone + Send + Sync {
@@ -34,10 +37,7 @@ pub trait EnumeratorTrait: Clone + Send + Sync {
 
     fn msg(&self) -> &str;
 
-    fn get_iter(
-        &self,
-        instance: &Self::Inst,
-    ) -> Box<dyn Iterator<Item = <Self::Inst as InstanceTrait>::StackElement>>;
+    fn get_iter(&self, instance: &Self::Inst) -> StackElementIter<Self::Inst>;
 }
 
 pub trait TacticTrait: Clone + Send + Sync {
@@ -129,7 +129,7 @@ impl<
         M: MapperTrait<Inst = I>,
     > Quantor<E, OE, T, M>
 {
-    fn formula(&self) -> &Box<Expression<E, OE, T, M>> {
+    fn formula(&self) -> &Expression<E, OE, T, M> {
         match self {
             // Quantor::All(_, t, _) => t,
             Quantor::AllOpt(_, t, _, _) => t,
@@ -183,7 +183,9 @@ impl<
                     })
                     .collect();
                 for node in nodes {
-                    proof.add_child(node);
+                    if !add_child_within_limit(&mut proof, node, stack) {
+                        break;
+                    }
                 }
             } else {
                 for case in case_iterator {
@@ -197,7 +199,7 @@ impl<
                     //     proof_item.add_payload(stack.get_profile(outcome.success()));
                     // }
 
-                    proof.add_child(proof_item);
+                    let within_limit = add_child_within_limit(&mut proof, proof_item, stack);
                     let res = outcome.success();
                     stack.pop();
 
@@ -210,7 +212,7 @@ impl<
                         Quantor::Any(_, _) => res,
                     };
 
-                    if should_break {
+                    if should_break || !within_limit {
                         break;
                     }
                 }
@@ -225,6 +227,21 @@ impl<
     }
 }
 
+/// Adds `child` to `proof`. If this exceeds the node limit of `stack`, adds a failing leaf instead and returns false.
+fn add_child_within_limit<I: InstanceTrait>(
+    proof: &mut ProofNode,
+    child: ProofNode,
+    stack: &I,
+) -> bool {
+    if stack.node_limit().is_none_or(|limit| limit.try_add()) {
+        proof.add_child(child);
+        true
+    } else {
+        proof.add_child(ProofNode::new_leaf("Size limit exceeded".into(), false));
+        false
+    }
+}
+
 pub fn map<E, OE, T, M>(mapper: M, expr: Expression<E, OE, T, M>) -> Expression<E, OE, T, M> {
     Expression::Map(mapper, Box::new(expr))
 }
@@ -1,10 +1,15 @@
-use std::{fmt::Display, fs::OpenOptions, path::PathBuf};
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+};
 
-use clap::{arg, Parser};
+use augmentation::proof_tree;
+use clap::Parser;
 
 pub use credit::*;
-use num_rational::Rational64;
-use path::{prove_nice_path_progress, PathProofOptions};
+use path::{print_enumeration_plan, prove_nice_path_progress, PathProofOptions, Tactic};
 
 use comps::*;
 
@@ -15,7 +20,6 @@ mod comps;
 mod credit;
 mod logic;
 mod path;
-mod proof_tree;
 mod types;
 
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, PartialEq, Eq, Hash)]
@@ -35,6 +39,15 @@ impl Node {
     pub fn c(id: u32) -> Self {
         Node::Comp(id)
     }
+    /// Returns the vertices `0, .., n-1`
+    pub fn node_range(n: u32) -> Vec<Node> {
+        (0..n).map(Node::Node).collect()
+    }
+    /// Returns the components `0, .., n-1`
+    #[cfg(test)]
+    pub fn comp_range(n: u32) -> Vec<Node> {
+        (0..n).map(Node::Comp).collect()
+    }
     pub fn set_id(&mut self, offset: u32) {
         match self {
             Node::Node(id) => *id = offset,
@@ -122,6 +135,38 @@ struct Path {
 
     #[clap(short = 'i', long = "initial_depth", default_value = "1")]
     initial_depth: u8,
+
+    /// Comma-separated order in which the tactics are tried, e.g. "fast_longer_path,local_merge,pendant,longer_path,cycle_merge"
+    #[clap(long, value_delimiter = ',')]
+    tactics: Option<Vec<Tactic>>,
+
+    /// Proves --steps evenly-spaced values of c from c_numer/c_demon to END_NUMER/END_DENOM
+    #[clap(long, num_args = 2, value_names = ["END_NUMER", "END_DENOM"], requires = "steps")]
+    credit_range: Option<Vec<i64>>,
+
+    /// Number of values of c proved with --credit-range
+    #[clap(long, requires = "credit_range")]
+    steps: Option<usize>,
+
+    /// Prints the distribution of leaf outcomes of every (pruned) proof tree
+    #[clap(long)]
+    stats: bool,
+
+    /// Stops growing the proof tree of a last component after this many nodes, failing the remaining cases
+    #[clap(long)]
+    max_nodes: Option<usize>,
+
+    /// Only prints the estimated number of cases per enumeration depth, without running the proof
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Checks necessary conditions of every successful tactic with an independent verifier
+    #[clap(long)]
+    verify: bool,
+
+    /// Enumerates a 3-matching of the last component before its type-specific edge configurations
+    #[clap(long)]
+    simple_case: bool,
 }
 
 #[derive(clap::ValueEnum, Clone)]
@@ -137,17 +182,71 @@ fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
     setup_logging(false)?;
 
+    // The first SIGINT stops the proof after the running cases are finished, the second one terminates immediately.
+    let timeout_flag = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register_conditional_shutdown(
+        signal_hook::consts::SIGINT,
+        1,
+        Arc::clone(&timeout_flag),
+    )?;
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&timeout_flag))?;
+
     match cli {
         //Cli::Tree(local) => prove_local(local), // the tree case is no longer needed
-        Cli::Path(path) => prove_path(path),
+        Cli::Path(path) => prove_path(path, timeout_flag)?,
     }
 
     Ok(())
 }
 
-fn prove_path(path: Path) {
-    let inv = CreditInv::new(Rational64::new(path.c_numer, path.c_demon).into());
+fn prove_path(path: Path, timeout_flag: Arc<AtomicBool>) -> anyhow::Result<()> {
+    let start = Credit::try_new(path.c_numer, path.c_demon)?;
+
+    let Some(range) = &path.credit_range else {
+        prove_path_for_inv(
+            &path,
+            CreditInv::new(start),
+            path.output_dir.clone(),
+            timeout_flag,
+        )?;
+        return Ok(());
+    };
+
+    let end = Credit::try_new(range[0], range[1])?;
+    let invs = CreditInv::for_range(start, end, path.steps.expect("clap requires --steps"))?;
+
+    let mut results = vec![];
+    for inv in invs {
+        if timeout_flag.load(std::sync::atomic::Ordering::SeqCst) {
+            break;
+        }
+        // every value of c gets its own directory
+        let output_dir = path.output_dir.join(inv.to_filename_suffix());
+        let proved = prove_path_for_inv(&path, inv.clone(), output_dir, timeout_flag.clone())?;
+        results.push((inv, proved));
+    }
+
+    if path.dry_run {
+        return Ok(());
+    }
+
+    println!("Summary:");
+    for (inv, proved) in results {
+        if proved {
+            println!("✔️ c = {}: complete proof", inv.c);
+        } else {
+            println!("❌ c = {}: no complete proof", inv.c);
+        }
+    }
+    Ok(())
+}
 
+fn prove_path_for_inv(
+    path: &Path,
+    inv: CreditInv,
+    output_dir: PathBuf,
+    timeout_flag: Arc<AtomicBool>,
+) -> anyhow::Result<bool> {
     // list of possible component types
     let comps = if inv.c < Credit::new(2, 7) {
         // if c < 2/7, we also need C7
@@ -165,18 +264,35 @@ fn prove_path(path: Path) {
         LastComp::L => large(),
     };
 
+    let options = PathProofOptions {
+        max_depth: path.max_depth,
+        initial_node_depth: path.initial_depth,
+        sc: path.sc,
+        tactic_ordering: path
+            .tactics
+            .clone()
+            .unwrap_or_else(Tactic::default_ordering),
+        stats: path.stats,
+        verify_mode: path.verify,
+        simple_case: path.simple_case,
+    };
+
+    if path.dry_run {
+        println!("Enumeration plan for c = {}:", inv.c);
+        print_enumeration_plan(comps, last_comp, &options);
+        return Ok(false);
+    }
+
     prove_nice_path_progress(
         comps,
         last_comp,
         &inv,
-        path.output_dir,
+        output_dir,
         path.output_depth,
-        PathProofOptions {
-            max_depth: path.max_depth,
-            initial_node_depth: path.initial_depth,
-            sc: path.sc,
-        },
+        options,
         path.parallel,
+        timeout_flag,
+        path.max_nodes,
     )
 }
 
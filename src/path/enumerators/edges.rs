@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use itertools::Itertools;
 
 use crate::comps::Component;
@@ -9,6 +11,41 @@ use crate::{
     Credit, Node,
 };
 
+type EdgeCheck = Box<
+    dyn Fn(
+        &Instance,
+        &[Option<Pidx>],
+        bool,
+    ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)>,
+>;
+
+/// The list of checks which are evaluated greedily by the edge enumerator. The first check that
+/// returns cases is used to split the current instance.
+pub struct GreedyEvalConfig {
+    checks: Vec<EdgeCheck>,
+}
+
+impl GreedyEvalConfig {
+    pub fn new() -> Self {
+        GreedyEvalConfig {
+            checks: vec![
+                Box::new(check_comp_config),
+                Box::new(check_comp_three_matching),
+                Box::new(check_three_matching),
+                Box::new(check_four_matching),
+                Box::new(check_comp_contractability),
+            ],
+        }
+    }
+
+    /// Prepends the simple case check, which enumerates a 3-matching of the last component before its
+    /// type-specific edge configurations are considered by `check_comp_config`.
+    pub fn with_sc(mut self) -> Self {
+        self.checks.insert(0, Box::new(check_simple_case));
+        self
+    }
+}
+
 // enumerate all new edges
 pub fn edge_enumerator(
     instance: &mut Instance,
@@ -24,18 +61,13 @@ pub fn edge_enumerator(
         }
     }
 
-    let res = greedy_evaluation(
-        instance,
-        &nodes_to_pidx,
-        finite,
-        vec![
-            Box::new(check_comp_config),
-            Box::new(check_comp_three_matching),
-            Box::new(check_three_matching),
-            Box::new(check_four_matching),
-            Box::new(check_comp_contractability),
-        ],
-    );
+    let config = if instance.context.simple_case {
+        GreedyEvalConfig::new().with_sc()
+    } else {
+        GreedyEvalConfig::new()
+    };
+
+    let res = greedy_evaluation(instance, &nodes_to_pidx, finite, config);
 
     if let Some((iter, name)) = res {
         let cases = iter.collect_vec();
@@ -48,19 +80,11 @@ pub fn edge_enumerator(
 
 fn greedy_evaluation(
     instance: &Instance,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     finite: bool,
-    funcs: Vec<
-        Box<
-            dyn Fn(
-                &Instance,
-                &Vec<Option<Pidx>>,
-                bool,
-            ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)>,
-        >,
-    >,
+    config: GreedyEvalConfig,
 ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
-    for f in funcs {
+    for f in config.checks {
         let res = f(instance, nodes_to_pidx, finite);
         if res.is_some() {
             return res;
@@ -70,9 +94,28 @@ fn greedy_evaluation(
     None
 }
 
+// Simple case: the last component gets a 3-matching to the rest of the instance, instead of the edge
+// configurations of check_comp_config.
+fn check_simple_case(
+    instance: &Instance,
+    nodes_to_pidx: &[Option<Pidx>],
+    finite: bool,
+) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
+    let last_comp = instance.first_comp().unwrap();
+    comp_three_matching(last_comp, instance, nodes_to_pidx, finite).map(|iter| {
+        (
+            iter,
+            format!(
+                "Simple case: 3-Matching of {}",
+                last_comp.path_idx.to_label()
+            ),
+        )
+    })
+}
+
 fn check_comp_three_matching(
     instance: &Instance,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     finite: bool,
 ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
     let path_comps = instance.path_nodes().collect_vec();
@@ -83,36 +126,44 @@ fn check_comp_three_matching(
         path_comps.iter().take(len - 1).collect_vec()
     };
     for path_comp in iter {
-        let idx = path_comp.path_idx;
-        let comp_nodes = path_comp.comp.nodes().to_vec();
-
-        if let Some(iter) = ensure_three_matching(comp_nodes, instance, finite) {
-            let iter = to_cases(iter, nodes_to_pidx, instance, true);
-            return Some((iter, format!("3-Matching of {}", idx)));
+        if let Some(iter) = comp_three_matching(path_comp, instance, nodes_to_pidx, finite) {
+            return Some((
+                iter,
+                format!("3-Matching of {}", path_comp.path_idx.to_label()),
+            ));
         }
     }
     None
 }
 
+/// Returns the cases for a 3-matching between `path_comp` and the rest of the instance, or `None` if there
+/// already is one.
+fn comp_three_matching(
+    path_comp: &PathComp,
+    instance: &Instance,
+    nodes_to_pidx: &[Option<Pidx>],
+    finite: bool,
+) -> Option<Box<dyn Iterator<Item = InstPart>>> {
+    let comp_nodes = path_comp.comp.nodes().to_vec();
+    ensure_three_matching(comp_nodes, instance, finite)
+        .map(|iter| to_cases(iter, nodes_to_pidx, instance, true))
+}
+
 // Check the edge configuration of the last component of the nice path, and add edges if necessary
 fn check_comp_config(
     instance: &Instance,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     _finite: bool,
 ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
     let path_comps = instance.path_nodes().collect_vec();
-    let comp = path_comps.first().unwrap();
+    let comp = instance.first_comp().unwrap();
     let incident_edges = instance
         .all_inter_comp_edges()
         .into_iter()
         .filter(|e| e.path_incident(comp.path_idx))
         .collect_vec();
 
-    let incident_back_edges = instance
-        .rem_edges()
-        .into_iter()
-        .filter(|e| e.source_idx == comp.path_idx)
-        .collect_vec();
+    let incident_back_edges = instance.rem_edges_from(comp.path_idx);
 
     let incident_out_edges = instance
         .out_edges()
@@ -124,13 +175,13 @@ fn check_comp_config(
         .iter()
         .skip(1)
         .flat_map(|p| p.comp.nodes().to_vec())
-        .filter(|n| n != &comp.in_node.unwrap())
+        .filter(|n| n != &comp.in_node_or_panic("contractable components"))
         .collect_vec();
 
     if incident_edges.len() == 1 && incident_out_edges.is_empty() && incident_back_edges.is_empty()
     {
         if comp.comp.is_c7() {
-            let in_node = comp.in_node.unwrap();
+            let in_node = comp.in_node_or_panic("contractable components");
             let nodes = comp.comp.nodes();
             assert!(nodes[0] == in_node);
 
@@ -147,7 +198,11 @@ fn check_comp_config(
 
             let mut all_cases: Box<dyn Iterator<Item = InstPart>> = Box::new(std::iter::empty());
             for config in configs {
-                let edge_iter = full_edge_iterator(config, complement.clone(), false, true, true);
+                let edge_iter = full_edge_iterator(
+                    config,
+                    NodeHitSpec::new(complement.clone()).with_rem(true),
+                    true,
+                );
                 let iter = to_cases_mul(edge_iter, nodes_to_pidx, instance, true);
                 all_cases = Box::new(all_cases.chain(iter));
             }
@@ -155,7 +210,7 @@ fn check_comp_config(
         }
 
         if comp.comp.is_c6() {
-            let in_node = comp.in_node.unwrap();
+            let in_node = comp.in_node_or_panic("contractable components");
             let nodes = comp.comp.nodes();
             assert!(nodes[0] == in_node);
 
@@ -169,7 +224,11 @@ fn check_comp_config(
 
             let mut all_cases: Box<dyn Iterator<Item = InstPart>> = Box::new(std::iter::empty());
             for config in configs {
-                let edge_iter = full_edge_iterator(config, complement.clone(), false, true, true); // TODO no outside edges!
+                let edge_iter = full_edge_iterator(
+                    config,
+                    NodeHitSpec::new(complement.clone()).with_rem(true),
+                    true,
+                ); // TODO no outside edges!
                 let iter = to_cases_mul(edge_iter, nodes_to_pidx, instance, true);
                 all_cases = Box::new(all_cases.chain(iter));
             }
@@ -177,7 +236,7 @@ fn check_comp_config(
         }
 
         if comp.comp.is_c5() {
-            let in_node = comp.in_node.unwrap();
+            let in_node = comp.in_node_or_panic("contractable components");
             let nodes = comp.comp.nodes();
             assert!(nodes[0] == in_node);
 
@@ -191,7 +250,11 @@ fn check_comp_config(
 
             let mut all_cases: Box<dyn Iterator<Item = InstPart>> = Box::new(std::iter::empty());
             for config in configs {
-                let edge_iter = full_edge_iterator(config, complement.clone(), true, true, true);
+                let edge_iter = full_edge_iterator(
+                    config,
+                    NodeHitSpec::new(complement.clone()).with_rem(true),
+                    true,
+                );
                 let iter = to_cases_mul(edge_iter, nodes_to_pidx, instance, true);
                 all_cases = Box::new(all_cases.chain(iter));
             }
@@ -199,7 +262,7 @@ fn check_comp_config(
         }
 
         if comp.comp.is_c4() {
-            let in_node = comp.in_node.unwrap();
+            let in_node = comp.in_node_or_panic("contractable components");
             let nodes = comp.comp.nodes();
             assert!(nodes[0] == in_node);
 
@@ -207,7 +270,11 @@ fn check_comp_config(
 
             let mut all_cases: Box<dyn Iterator<Item = InstPart>> = Box::new(std::iter::empty());
             for config in configs {
-                let edge_iter = full_edge_iterator(config, complement.clone(), true, true, true);
+                let edge_iter = full_edge_iterator(
+                    config,
+                    NodeHitSpec::new(complement.clone()).with_rem(true),
+                    true,
+                );
                 let iter = to_cases_mul(edge_iter, nodes_to_pidx, instance, true);
                 all_cases = Box::new(all_cases.chain(iter));
             }
@@ -219,7 +286,7 @@ fn check_comp_config(
 
 fn check_three_matching(
     instance: &Instance,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     finite: bool,
 ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
     let path_comps = instance.path_nodes().collect_vec();
@@ -446,55 +513,49 @@ fn check_three_matching(
 //     None
 // }
 
+/// Ensures a 4-matching between every set of at least two non-large path components with at least 10 vertices
+/// and the rest of the instance.
+///
+/// This check runs after `check_three_matching` in `greedy_evaluation`, which considers the same sets of components.
+/// Hence, we can assume that a 3-matching already exists for each of these sets.
 fn check_four_matching(
     instance: &Instance,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     finite: bool,
 ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
     let path_comps = instance.path_nodes().collect_vec();
     let len = path_comps.len();
 
-    if finite {
-        let path_comps = instance.path_nodes().collect_vec();
+    let path_comps = if finite {
+        path_comps
+    } else {
+        path_comps.into_iter().take(len - 2).collect_vec()
+    };
 
-        for left_side in path_comps.into_iter().powerset().filter(|p| p.len() >= 2) {
-            let comp_nodes = left_side
-                .iter()
-                .flat_map(|c| c.comp.nodes().to_vec())
-                .collect_vec();
+    // sets containing a large component are skipped anyway
+    let candidates = path_comps
+        .into_iter()
+        .filter(|c| !c.comp.is_large())
+        .collect_vec();
 
-            let left_size: usize = left_side.iter().map(|comp| comp.comp.num_vertices()).sum();
+    // if all candidates together have less than 10 vertices, no subset can be large enough
+    let total_size: usize = candidates.iter().map(|c| c.comp.num_vertices()).sum();
+    if total_size < 10 {
+        return None;
+    }
 
-            let left_large = left_side.iter().any(|c| c.comp.is_large());
+    for left_side in candidates.into_iter().powerset().filter(|p| p.len() >= 2) {
+        let left_size: usize = left_side.iter().map(|comp| comp.comp.num_vertices()).sum();
 
-            if left_size >= 10 && !left_large {
-                if let Some(iter) = ensure_k_matching(comp_nodes, instance, 4, finite) {
-                    let iter = to_cases(iter, nodes_to_pidx, instance, true);
-                    return Some((iter, "4-Matching".to_string()));
-                }
-            }
-        }
-    } else {
-        for left_side in path_comps
-            .into_iter()
-            .take(len - 2)
-            .powerset()
-            .filter(|p| p.len() >= 2)
-        {
+        if left_size >= 10 {
             let comp_nodes = left_side
                 .iter()
                 .flat_map(|c| c.comp.nodes().to_vec())
                 .collect_vec();
 
-            let left_size: usize = left_side.iter().map(|comp| comp.comp.num_vertices()).sum();
-
-            let left_large = left_side.iter().any(|c| c.comp.is_large());
-
-            if left_size >= 10 && !left_large {
-                if let Some(iter) = ensure_k_matching(comp_nodes, instance, 4, finite) {
-                    let iter = to_cases(iter, nodes_to_pidx, instance, true);
-                    return Some((iter, "4-Matching".to_string()));
-                }
+            if let Some(iter) = ensure_k_matching(comp_nodes, instance, 4, finite) {
+                let iter = to_cases(iter, nodes_to_pidx, instance, true);
+                return Some((iter, "4-Matching".to_string()));
             }
         }
     }
@@ -504,7 +565,7 @@ fn check_four_matching(
 
 fn check_comp_contractability(
     instance: &Instance,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     finite: bool,
 ) -> Option<(Box<dyn Iterator<Item = InstPart>>, String)> {
     let path_comps = instance.path_nodes().collect_vec();
@@ -515,7 +576,6 @@ fn check_comp_contractability(
     } else {
         path_comps.iter().take(len - 1).collect_vec()
     };
-    let contractability_checked = instance.contractability_checked().collect_vec();
     for path_comp in iter {
         // TODO filter this differently
         if !(path_comp.comp.is_c4()
@@ -523,20 +583,21 @@ fn check_comp_contractability(
             || (path_comp.comp.is_c5()
                 && !path_comp.used
                 && path_comp.path_idx.is_prelast()
-                && !path_comp
-                    .comp
-                    .is_adjacent(&path_comp.in_node.unwrap(), &path_comp.out_node.unwrap())))
-            && (!contractability_checked.contains(&&path_comp.path_idx))
+                && !path_comp.comp.is_adjacent(
+                    &path_comp.in_node_or_panic("contractable components"),
+                    &path_comp.out_node_or_panic("contractable components"),
+                ))
+            || instance.is_contractability_checked(path_comp.path_idx))
         {
             if let Some(iter) =
-                handle_contractable_components(path_comp, instance, finite, nodes_to_pidx.clone())
+                handle_contractable_components(path_comp, instance, finite, nodes_to_pidx.to_vec())
             {
                 let idx = path_comp.path_idx;
                 let iter = Box::new(iter.map(move |mut part| {
                     part.contractability_checked.push(idx);
                     part
                 }));
-                return Some((iter, format!("Contractablility of {}", idx)));
+                return Some((iter, format!("Contractablility of {}", idx.to_label())));
             }
         }
     }
@@ -575,7 +636,7 @@ fn compute_good_edges(
 
 fn to_cases_mul(
     iter: Box<dyn Iterator<Item = Vec<(Node, Hit)>>>,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     instance: &Instance,
     matching: bool,
 ) -> Box<dyn Iterator<Item = InstPart>> {
@@ -590,7 +651,7 @@ fn to_cases_mul(
 
 fn to_cases(
     iter: Box<dyn Iterator<Item = (Node, Hit)>>,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     instance: &Instance,
     matching: bool,
 ) -> Box<dyn Iterator<Item = InstPart>> {
@@ -605,7 +666,7 @@ fn to_cases(
 
 fn to_cases_with_edge_cost(
     iter: Box<dyn Iterator<Item = (Node, Hit)>>,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     instance: &Instance,
     cost: Credit,
     matching: bool,
@@ -616,7 +677,7 @@ fn to_cases_with_edge_cost(
 
 fn to_cases_with_edge_cost_mul(
     iter: Box<dyn Iterator<Item = Vec<(Node, Hit)>>>,
-    nodes_to_pidx: &Vec<Option<Pidx>>,
+    nodes_to_pidx: &[Option<Pidx>],
     instance: &Instance,
     cost: Credit,
     matching: bool,
@@ -626,7 +687,7 @@ fn to_cases_with_edge_cost_mul(
     let good_edges = instance.good_edges().into_iter().cloned().collect_vec();
     let good_out = instance.good_out().into_iter().cloned().collect_vec();
 
-    let nodes_to_pidx = nodes_to_pidx.clone();
+    let nodes_to_pidx = nodes_to_pidx.to_vec();
 
     let new_rem_id = instance.new_rem_id();
 
@@ -640,17 +701,16 @@ fn to_cases_with_edge_cost_mul(
             new_rem_ids.push(temp);
         }
 
-        for ((node, hit), id) in new_edges.into_iter().zip(new_rem_ids.into_iter()) {
+        for ((node, hit), id) in new_edges.into_iter().zip(new_rem_ids) {
             match hit {
-                Hit::Outside => part.out_edges.push(node),
                 Hit::RemPath => {
-                    part.rem_edges.push(HalfAbstractEdge {
-                        source: node,
-                        source_idx: nodes_to_pidx[node.get_id() as usize].unwrap(),
-                        cost,
-                        id,
-                        matching,
-                    });
+                    part.rem_edges.push(
+                        HalfAbstractEdge::new(node, nodes_to_pidx[node.get_id() as usize].unwrap())
+                            .cost(cost)
+                            .id(id)
+                            .matching(matching)
+                            .build(),
+                    );
                 }
                 Hit::Node(hit_node) => {
                     if nodes_to_pidx[node.get_id() as usize].unwrap()
@@ -674,20 +734,15 @@ fn to_cases_with_edge_cost_mul(
         if part.is_empty() {
             None
         } else {
+            if cfg!(debug_assertions) {
+                part.assert_no_duplicate_edges();
+            }
             Some(part)
         }
     }));
 
     // Filter: consider only cases where edge are _not_ already good.
-    Box::new(iter.filter(move |part| {
-        if !part.edges.is_empty() {
-            part.edges.iter().all(|edge| !good_edges.contains(edge))
-        } else if !part.out_edges.is_empty() {
-            part.out_edges.iter().all(|edge| !good_out.contains(edge))
-        } else {
-            true
-        }
-    }))
+    Box::new(iter.filter(move |part| !part.is_subsumed_by_good_edges(&good_edges, &good_out)))
 }
 
 /// This method assumes that the given component already has a 3-matching.
@@ -724,11 +779,7 @@ fn handle_contractable_components(
 
     // free_nodes = nodes - used_nodes
     // have currently no incident edges
-    let free_nodes = nodes
-        .iter()
-        .filter(|n| !used_nodes.contains(n))
-        .cloned()
-        .collect_vec();
+    let free_nodes = comp.complement_nodes(&used_nodes);
 
     if free_nodes.len() <= 1 {
         // Not contractable
@@ -758,7 +809,9 @@ fn handle_contractable_components(
 
         if comp.is_c5() {
             assert_eq!(free_nodes.len(), 2);
-            assert!(!comp.is_adjacent(&free_nodes[0], &free_nodes[1]));
+            // f1 and f2 are at distance two on one side of the cycle and at distance three on the other side
+            let (d1, d2) = comp.cycle_distance(free_nodes[0], free_nodes[1]);
+            assert_eq!(d1.min(d2), 2);
 
             //
             //     v3
@@ -769,11 +822,7 @@ fn handle_contractable_components(
             //
             let f1 = free_nodes[0];
             let f2 = free_nodes[1];
-            let v3 = *comp
-                .nodes()
-                .iter()
-                .find(|v| comp.is_adjacent(v, &f1) && comp.is_adjacent(v, &f2))
-                .unwrap();
+            let v3 = comp.vertices_adjacent_to_both(f1, f2)[0];
             let v1 = *comp
                 .nodes()
                 .iter()
@@ -798,7 +847,7 @@ fn handle_contractable_components(
 
             // Case b) non-inside edges from f1 and f2
             let case_b = to_cases(
-                edge_iterator(free_nodes, complement, true, !finite),
+                edge_iterator(free_nodes, NodeHitSpec::new(complement).with_rem(!finite)),
                 &nodes_to_pidx,
                 instance,
                 false,
@@ -823,28 +872,16 @@ fn handle_contractable_components(
                 //  v3    v2
                 //   \    /
                 //     f3
-                let v1 = *comp
-                    .nodes()
-                    .iter()
-                    .find(|v| comp.is_adjacent(v, &f1) && comp.is_adjacent(v, &f2))
-                    .unwrap();
-                let v2 = *comp
-                    .nodes()
-                    .iter()
-                    .find(|v| comp.is_adjacent(v, &f2) && comp.is_adjacent(v, &f3))
-                    .unwrap();
-                let v3 = *comp
-                    .nodes()
-                    .iter()
-                    .find(|v| comp.is_adjacent(v, &f1) && comp.is_adjacent(v, &f3))
-                    .unwrap();
+                let v1 = comp.vertices_adjacent_to_both(f1, f2)[0];
+                let v2 = comp.vertices_adjacent_to_both(f2, f3)[0];
+                let v3 = comp.vertices_adjacent_to_both(f1, f3)[0];
 
                 // Case a) new nice pairs
                 let case_a = vec![InstPart::new_nice_pairs(vec![(v1, v3), (v2, v3), (v1, v2)])];
 
                 // Case b) edges from f1 and f2 and f3
                 let case_b = to_cases(
-                    edge_iterator(free_nodes, complement, true, !finite),
+                    edge_iterator(free_nodes, NodeHitSpec::new(complement).with_rem(!finite)),
                     &nodes_to_pidx,
                     instance,
                     false,
@@ -906,11 +943,7 @@ fn handle_contractable_components(
                         && (comp.is_adjacent(v, &f1) || comp.is_adjacent(v, &f2))
                 })
                 .unwrap();
-            let v2 = *comp
-                .nodes()
-                .iter()
-                .find(|v| comp.is_adjacent(v, &v1) && comp.is_adjacent(v, &f3))
-                .unwrap();
+            let v2 = comp.vertices_adjacent_to_both(v1, f3)[0];
 
             // Case a) new nice pairs between v1,v2,v3
             let case_a = vec![
@@ -920,7 +953,7 @@ fn handle_contractable_components(
 
             // Case b) edges from f1 and f2 and f3
             let case_b = to_cases(
-                edge_iterator(free_nodes, complement, true, !finite),
+                edge_iterator(free_nodes, NodeHitSpec::new(complement).with_rem(!finite)),
                 &nodes_to_pidx,
                 instance,
                 false,
@@ -971,7 +1004,10 @@ fn handle_contractable_components(
                     .collect_vec();
 
                 // Case b) edges from free nodes
-                let iter = edge_iterator(free_nodes.clone(), complement.clone(), true, !finite);
+                let iter = edge_iterator(
+                    free_nodes.clone(),
+                    NodeHitSpec::new(complement.clone()).with_rem(!finite),
+                );
                 let comp = comp.clone();
                 let iter = iter.flat_map(move |(node, hit)| {
                     if free_nodes.len() - 1 == 3
@@ -988,9 +1024,12 @@ fn handle_contractable_components(
                             .filter(|f| f != &&node)
                             .cloned()
                             .collect_vec();
-                        edge_iterator(other_free_nodes, complement.clone(), true, !finite)
-                            .map(|h| vec![(node, hit), h])
-                            .collect_vec()
+                        edge_iterator(
+                            other_free_nodes,
+                            NodeHitSpec::new(complement.clone()).with_rem(!finite),
+                        )
+                        .map(|h| vec![(node, hit), h])
+                        .collect_vec()
                     } else {
                         vec![vec![(node, hit)]]
                     }
@@ -1020,9 +1059,30 @@ fn ensure_three_matching(
     instance: &Instance,
     finite: bool,
 ) -> Option<Box<dyn Iterator<Item = (Node, Hit)>>> {
+    // Fast path: three distinct vertices with outside or REM edges are already counted as a 3-matching by
+    // ensure_k_matching, so no new edges are needed.
+    if non_comp_nodes_with_out_or_rem(&set1, instance).len() >= 3 {
+        return None;
+    }
+
     ensure_k_matching(set1, instance, 3, finite)
 }
 
+/// Unique non-comp nodes in `set` which have an outgoing or REM edge.
+fn non_comp_nodes_with_out_or_rem(set: &[Node], instance: &Instance) -> Vec<Node> {
+    let outside_edges = instance.out_edges();
+    let rem_sources = instance
+        .rem_edges()
+        .into_iter()
+        .map(|e| e.source)
+        .collect_vec();
+    set.iter()
+        .filter(|n| !n.is_comp() && (outside_edges.contains(n) || rem_sources.contains(n)))
+        .unique()
+        .cloned()
+        .collect_vec()
+}
+
 /// Assumed that set1 is composed of all nodes of path comps
 fn ensure_k_matching(
     set1: Vec<Node>,
@@ -1056,14 +1116,7 @@ fn ensure_k_matching(
 
     // 1. step: Compute and count unique non-comp nodes in set with outgoing or REM edges.
     // these are counted as matching edges!
-    let non_comp_out_or_rem = set1
-        .iter()
-        .filter(|n| {
-            !n.is_comp() && (outside_edges_at_set.contains(n) || rem_edges_at_set.contains(n))
-        })
-        .unique()
-        .cloned()
-        .collect_vec();
+    let non_comp_out_or_rem = non_comp_nodes_with_out_or_rem(&set1, instance);
 
     // 2. step: Compute and count outgoing and REM edges at comp nodes in set.
     // these are counted as matching edges!
@@ -1149,23 +1202,43 @@ fn ensure_k_matching(
             })
             .collect_vec();
 
-        return Some(edge_iterator(free_set, free_complement, true, !finite));
+        return Some(edge_iterator(
+            free_set,
+            NodeHitSpec::new(free_complement).with_rem(!finite),
+        ));
     }
 
     None
 }
 
-fn edge_iterator(
-    node_set: Vec<Node>,
+/// Describes which endpoints new edges may have.
+///
+/// Edges leaving the nice path are never enumerated here; outside edges only enter an instance
+/// when `Mapper::ToFiniteInstance` turns the REM edges into outside edges.
+struct NodeHitSpec {
+    /// The already enumerated nodes which can be hit
     hit_set: Vec<Node>,
-    with_outside: bool,
-    with_rem: bool,
-) -> Box<dyn Iterator<Item = (Node, Hit)>> {
-    let mut hits = hit_set.into_iter().map(Hit::Node).collect_vec();
-    // if with_outside {
-    //     hits.push(Hit::Outside);
-    // }
-    if with_rem {
+    /// Whether edges can hit the remaining path
+    include_rem: bool,
+}
+
+impl NodeHitSpec {
+    fn new(hit_set: Vec<Node>) -> Self {
+        NodeHitSpec {
+            hit_set,
+            include_rem: false,
+        }
+    }
+
+    fn with_rem(mut self, include_rem: bool) -> Self {
+        self.include_rem = include_rem;
+        self
+    }
+}
+
+fn edge_iterator(node_set: Vec<Node>, spec: NodeHitSpec) -> Box<dyn Iterator<Item = (Node, Hit)>> {
+    let mut hits = spec.hit_set.into_iter().map(Hit::Node).collect_vec();
+    if spec.include_rem {
         hits.push(Hit::RemPath);
     }
 
@@ -1173,10 +1246,8 @@ fn edge_iterator(
     Box::new(iter)
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Hit {
-    // Edge goes outside of nice path
-    Outside,
     // Edge hits somewhere later in the nice path, which we do not have enumerated yet
     RemPath,
     // Edge hits a specific already enumerated node
@@ -1221,26 +1292,27 @@ impl Iterator for EdgeIterator {
             Some((self.nodes[0], self.hits[0]))
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let total = self.nodes.len() * self.hits.len();
+        let consumed = match self.current {
+            Some((c_node, c_hit)) => c_node * self.hits.len() + c_hit + 1,
+            None => 0,
+        };
+        let remaining = total - consumed;
+        (remaining, Some(remaining))
+    }
 }
 
+impl ExactSizeIterator for EdgeIterator {}
+
 fn full_edge_iterator(
     node_set: Vec<Node>,
-    hit_set: Vec<Node>,
-    with_outside: bool,
-    with_rem: bool,
+    spec: NodeHitSpec,
     matching: bool,
 ) -> Box<dyn Iterator<Item = Vec<(Node, Hit)>>> {
-    let mut hits = hit_set.into_iter().map(Hit::Node).collect_vec();
-    // if with_outside {
-    //     if matching {
-    //         for _ in &node_set {
-    //             hits.push(Hit::Outside);
-    //         }
-    //     } else {
-    //         hits.push(Hit::Outside);
-    //     }
-    // }
-    if with_rem {
+    let mut hits = spec.hit_set.into_iter().map(Hit::Node).collect_vec();
+    if spec.include_rem {
         if matching {
             for _ in &node_set {
                 hits.push(Hit::RemPath);
@@ -1261,6 +1333,15 @@ struct FullEdgeIterator {
     source_iter: Option<Box<dyn Iterator<Item = Vec<Node>>>>,
     hit_iter: Option<Box<dyn Iterator<Item = Vec<Hit>>>>,
     matching: bool,
+    // sorted edge sets which were already returned
+    seen: HashSet<Vec<(Node, Hit)>>,
+}
+
+/// A clone starts the enumeration from the beginning, independent of how far `self` has progressed.
+impl Clone for FullEdgeIterator {
+    fn clone(&self) -> Self {
+        Self::new(self.nodes.clone(), self.hits.clone(), self.matching)
+    }
 }
 
 impl FullEdgeIterator {
@@ -1273,14 +1354,11 @@ impl FullEdgeIterator {
             source_iter: None,
             hit_iter: None,
             matching,
+            seen: HashSet::new(),
         }
     }
-}
-
-impl Iterator for FullEdgeIterator {
-    type Item = Vec<(Node, Hit)>;
 
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_config(&mut self) -> Option<Vec<(Node, Hit)>> {
         let len = self.nodes.len();
         if self.source_iter.is_none() {
             self.source_iter = Some(Box::new(self.nodes.clone().into_iter().permutations(len)));
@@ -1343,3 +1421,140 @@ impl Iterator for FullEdgeIterator {
         None
     }
 }
+
+impl Iterator for FullEdgeIterator {
+    type Item = Vec<(Node, Hit)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let config = self.next_config()?;
+            if !self.matching {
+                return Some(config);
+            }
+            // Identical source nodes (e.g. of a large component) or hits (e.g. RemPath) would
+            // otherwise produce the same edge set multiple times.
+            let mut key = config.clone();
+            key.sort();
+            if self.seen.insert(key) {
+                return Some(config);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_edge_iterator {
+    use super::*;
+
+    #[test]
+    fn test_len() {
+        let hits = vec![Hit::Node(Node::n(10)), Hit::Node(Node::n(11)), Hit::RemPath];
+        let iter = EdgeIterator::new(Node::node_range(4), hits);
+        assert_eq!(iter.len(), 12);
+        assert_eq!(iter.count(), 12);
+    }
+
+    #[test]
+    fn test_len_while_iterating() {
+        let hits = vec![Hit::Node(Node::n(10)), Hit::RemPath];
+        let mut iter = EdgeIterator::new(Node::node_range(3), hits);
+        for remaining in (0..6).rev() {
+            iter.next().unwrap();
+            assert_eq!(iter.len(), remaining);
+        }
+        assert!(iter.next().is_none());
+        assert_eq!(iter.len(), 0);
+    }
+
+    #[test]
+    fn test_len_empty() {
+        assert_eq!(EdgeIterator::new(vec![], vec![Hit::RemPath]).len(), 0);
+        assert_eq!(EdgeIterator::new(Node::node_range(3), vec![]).len(), 0);
+    }
+
+    #[test]
+    fn test_full_edge_iterator_no_duplicates() {
+        // two edges of a large component which both go to the remaining path
+        let large = vec![Node::c(0), Node::c(0)];
+        let hits = vec![Hit::RemPath, Hit::RemPath];
+        let configs = FullEdgeIterator::new(large, hits, true).collect_vec();
+        assert_eq!(configs.len(), 1);
+
+        let hits = vec![Hit::Node(Node::n(10)), Hit::Node(Node::n(11)), Hit::RemPath];
+        let configs = FullEdgeIterator::new(Node::node_range(2), hits, true).collect_vec();
+        // 2 orderings of the sources for each of the 3 pairs of hits
+        assert_eq!(configs.len(), 6);
+        assert!(configs
+            .iter()
+            .map(|c| c.iter().sorted().cloned().collect_vec())
+            .all_unique());
+    }
+
+    #[test]
+    fn test_full_edge_iterator_clone() {
+        let hits = vec![Hit::Node(Node::n(10)), Hit::Node(Node::n(11)), Hit::RemPath];
+        let mut iter = FullEdgeIterator::new(Node::node_range(2), hits, true);
+        let first = iter.next().unwrap();
+
+        // the clone starts again from the first configuration
+        let configs = iter.clone().collect_vec();
+        assert_eq!(configs.len(), 6);
+        assert_eq!(configs[0], first);
+        assert_eq!(iter.count(), 5);
+    }
+}
+
+#[cfg(test)]
+mod test_four_matching {
+    use super::*;
+    use crate::{
+        comps::{c4, c5},
+        path::instance::InstanceContext,
+        util::relabels_nodes_sequentially,
+        CreditInv,
+    };
+
+    /// A finite instance whose path consists of the given components and has no edges.
+    fn instance_with_comps(mut comps: Vec<Component>) -> (Instance, Vec<Option<Pidx>>) {
+        relabels_nodes_sequentially(&mut comps, 0);
+        let mut instance = Instance {
+            stack: vec![],
+            context: InstanceContext::builder()
+                .inv(CreditInv::new(Credit::new(2, 7)))
+                .build(),
+        };
+        for comp in comps {
+            instance.extend_with_comp(PathComp {
+                in_node: Some(comp.fixed_node()),
+                out_node: Some(comp.fixed_node()),
+                initial_nps: comp.edges(),
+                comp,
+                used: false,
+                path_idx: Pidx::Last,
+            });
+        }
+
+        let mut nodes_to_pidx = vec![None; 20];
+        for path_comp in instance.path_nodes() {
+            for node in path_comp.comp.nodes() {
+                nodes_to_pidx[node.get_id() as usize] = Some(path_comp.path_idx);
+            }
+        }
+        (instance, nodes_to_pidx)
+    }
+
+    #[test]
+    fn test_two_c5() {
+        // 10 vertices without any edges cannot have a 4-matching to the rest of the graph yet
+        let (instance, nodes_to_pidx) = instance_with_comps(vec![c5(), c5()]);
+        let (_, msg) = check_four_matching(&instance, &nodes_to_pidx, true).unwrap();
+        assert_eq!(msg, "4-Matching");
+    }
+
+    #[test]
+    fn test_c5_c4() {
+        // 9 vertices are not enough for requiring a 4-matching
+        let (instance, nodes_to_pidx) = instance_with_comps(vec![c5(), c4()]);
+        assert!(check_four_matching(&instance, &nodes_to_pidx, true).is_none());
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
         PathComp, Pidx,
     },
     types::Edge,
-    util::relabels_nodes_sequentially,
+    util::{assert_unique_node_ids, relabels_nodes_sequentially},
 };
 
 /// Splits the current pattern by adding one more component and considering all feasible in- out-
@@ -28,10 +28,17 @@ pub fn path_comp_enumerator(instance: &Instance) -> Box<dyn Iterator<Item = Inst
         let mut new_comps = vec![comp];
         relabels_nodes_sequentially(&mut new_comps, num_used_labels);
         let comp = new_comps.remove(0);
-        let node = match new_comp {
-            PathNode::Used(_) => PathNode::Used(comp.clone()),
-            PathNode::Unused(_) => PathNode::Unused(comp.clone()),
-        };
+        // relabeling only checks the new component, so check it against the components of the pattern as well
+        if cfg!(debug_assertions) {
+            let all_comps = pattern_comps
+                .iter()
+                .map(|c| c.comp.clone())
+                .chain(std::iter::once(comp.clone()))
+                .collect_vec();
+            assert_unique_node_ids(&all_comps);
+        }
+        let mut node = PathNode::Unused(comp.clone());
+        node.set_used(new_comp.is_used());
 
         // compute index for new comp
         let new_node_idx = pattern_comps.last().unwrap().path_idx.prec();
@@ -92,11 +99,11 @@ fn split_cases_by_required_nice_pairs(mut path_comp: PathComp) -> impl Iterator<
     let comp = &path_comp.comp;
     let used = path_comp.used;
     let idx = path_comp.path_idx;
-    let in_node = path_comp.in_node.unwrap();
-    let out_node = path_comp.out_node.unwrap();
+    let in_node = path_comp.in_node_or_panic("required nice pairs");
+    let out_node = path_comp.out_node_or_panic("required nice pairs");
 
-    // if in and out are adjacent we already have a nice pair
-    if !comp.is_adjacent(&in_node, &out_node) {
+    // if there is a Hamiltonian path from in to out we already have a nice pair
+    if !comp.is_path_nice_pair(in_node, out_node) {
         if comp.is_c4() {
             path_comp.initial_nps.push((in_node, out_node));
         }
@@ -152,9 +159,14 @@ pub fn path_extension_enumerator(
     // Enumerate back edges which might hit or don't hit the new component
     let iter: Box<dyn Iterator<Item = InstPart>> =
         Box::new(iter.into_iter().flat_map(move |inst_part| {
+            // parts which do not add a component cannot be hit by back edges
+            if inst_part.is_edge_only() {
+                return Box::new(std::iter::once(inst_part)) as Box<dyn Iterator<Item = InstPart>>;
+            }
+
             //let pattern_comps = pattern_comps.clone();
             let back_edges = back_edges.iter().cloned().collect_vec();
-            back_edges
+            let iter = back_edges
                 .into_iter()
                 .powerset()
                 .flat_map(move |hitting_back_edges| {
@@ -165,8 +177,7 @@ pub fn path_extension_enumerator(
                     let mut iter: Box<dyn Iterator<Item = InstPart>> =
                         Box::new(vec![InstPart::new_path_comp(path_comp.clone())].into_iter());
 
-                    for i in 0..old_pattern_len {
-                        let source_idx = Pidx::from(i);
+                    for source_idx in Pidx::all_up_to(old_pattern_len) {
                         //let source_comp = pattern_comps[source_idx.raw()].comp.clone();
                         let comp = path_comp.comp.clone();
 
@@ -187,6 +198,8 @@ pub fn path_extension_enumerator(
 
                         // First enumerate matching edges
                         if !matching_hit_back.is_empty() {
+                            // the matching nodes are chosen from the cycle of the new component
+                            debug_assert!(comp.is_large() || comp.is_regular(2));
                             iter = Box::new(iter.flat_map(move |inst_part| {
                                 let matching_hit_back = matching_hit_back.clone();
 
@@ -279,8 +292,44 @@ pub fn path_extension_enumerator(
                     }
 
                     iter
-                })
+                });
+            Box::new(iter)
         }));
 
     Some((Box::new(iter), "new path node".into()))
 }
+
+#[cfg(test)]
+mod test_path_extension {
+    use super::*;
+    use crate::{comps::c5, path::instance::InstanceContext, Credit, CreditInv};
+
+    #[test]
+    fn test_extend_beyond_number_of_comp_types() {
+        // the context only has a single component type, but the path must still be extended further
+        let mut comps = vec![c5(), c5()];
+        relabels_nodes_sequentially(&mut comps, 0);
+        let mut instance = Instance {
+            stack: vec![],
+            context: InstanceContext::builder()
+                .inv(CreditInv::new(Credit::new(2, 7)))
+                .add_comp(PathNode::Unused(c5()))
+                .build(),
+        };
+        for comp in comps {
+            instance.extend_with_comp(PathComp {
+                in_node: Some(comp.fixed_node()),
+                out_node: Some(comp.fixed_node()),
+                initial_nps: comp.edges(),
+                comp,
+                used: false,
+                path_idx: Pidx::Last,
+            });
+        }
+
+        let (mut iter, _) = path_extension_enumerator(&mut instance).unwrap();
+        let part = iter.next().unwrap();
+        assert_eq!(part.path_nodes.len(), 1);
+        assert_eq!(part.path_nodes[0].path_idx, Pidx::N(2));
+    }
+}
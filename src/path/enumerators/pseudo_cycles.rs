@@ -4,7 +4,7 @@ use crate::{
     path::{
         instance::Instance,
         pseudo_cycle::{CycleComp, PseudoCycle},
-        EdgeId, HalfAbstractEdge, PathComp,
+        HalfAbstractEdge, PathComp,
     },
     types::Edge,
     util::product_of_first,
@@ -16,21 +16,21 @@ pub fn enumerate_pseudo_cycles(
     instance: &Instance,
     bounded: bool,
 ) -> Box<dyn Iterator<Item = PseudoCycle>> {
+    if instance.path_len() < 3 {
+        return Box::new(std::iter::empty());
+    }
+
     let pattern_comps = instance.path_nodes().cloned().collect_vec();
     let pattern_edges = instance.all_inter_comp_edges();
     let mut back_edges = instance.rem_edges();
     let last_comp = pattern_comps.last().cloned().unwrap();
-    back_edges.push(HalfAbstractEdge {
-        source: last_comp.in_node.unwrap(),
-        source_idx: last_comp.path_idx,
-        cost: Credit::from_integer(1),
-        id: EdgeId(0),
-        matching: false,
-    });
-
-    if pattern_comps.len() < 3 {
-        return Box::new(std::iter::empty());
-    }
+    back_edges.push(
+        HalfAbstractEdge::new(
+            last_comp.in_node_or_panic("pseudo cycles"),
+            last_comp.path_idx,
+        )
+        .build(),
+    );
 
     let mut iter: Box<dyn Iterator<Item = PseudoCycle>> = Box::new(std::iter::empty());
     for i in 3..=(pattern_comps.len() + 1) {
@@ -54,13 +54,11 @@ fn edges_between(
     i2: &CycleComp,
 ) -> Vec<((Node, Node), Credit)> {
     match (i1, i2) {
-        (CycleComp::PathComp(idx1), CycleComp::PathComp(idx2)) => {
-            return edges
-                .iter()
-                .filter(|e| e.between_path_nodes(*idx1, *idx2))
-                .map(|e| (e.nodes_between_path_nodes(*idx1, *idx2), e.cost))
-                .collect_vec();
-        }
+        (CycleComp::PathComp(idx1), CycleComp::PathComp(idx2)) => edges
+            .iter()
+            .filter(|e| e.between_path_nodes(*idx1, *idx2))
+            .map(|e| (e.nodes_between_path_nodes(*idx1, *idx2), e.cost))
+            .collect_vec(),
         (CycleComp::PathComp(idx), CycleComp::Rem) => rem_edges
             .iter()
             .filter(|e| e.source_idx == *idx)
@@ -99,6 +97,7 @@ fn pseudo_cycles_of_length(
         .permutations(length)
         .filter(|perm| perm.iter().min() == perm.first())
         .flat_map(move |perm| {
+            let pattern_comps = pattern_comps.clone();
             let first = perm[0].clone();
             let sets_of_in_between_edges = [perm.clone(), vec![first]]
                 .concat()
@@ -135,10 +134,7 @@ fn pseudo_cycles_of_length(
                         .collect_vec();
 
                     // cycle nodes:   [0.out -- 1.in:1.out -- 2.in:2.out -- 3.in:3.out -- 0.in]
-                    Some(PseudoCycle {
-                        cycle,
-                        total_edge_cost,
-                    })
+                    Some(PseudoCycle::new(cycle, total_edge_cost, &pattern_comps))
                 } else {
                     None
                 }
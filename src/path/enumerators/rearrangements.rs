@@ -10,6 +10,10 @@ use crate::{
 };
 
 /// Enumerates all possible rearrangements based on the current pseudo cycle on the stack.
+///
+/// Invariant: each yielded extension corresponds to a valid re-ordering of the current path that may enable
+/// `check_longer_nice_path` to succeed. In the infinite case, an extension starts at the newest component of the
+/// cycle, in the finite case at any component. For every start, both directions of the cycle are yielded.
 pub fn enumerate_rearrangements(
     instance: &Instance,
     finite: bool,
@@ -159,7 +163,7 @@ pub fn enumerate_rearrangements(
     }
 }
 
-pub fn fix_in_out_direction(extension: &mut Vec<(Node, CycleComp, Node)>) {
+pub fn fix_in_out_direction(extension: &mut [(Node, CycleComp, Node)]) {
     extension.iter_mut().for_each(|(n1, _c, n2)| {
         std::mem::swap(&mut (*n1), &mut (*n2));
     });
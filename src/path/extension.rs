@@ -16,6 +16,29 @@ pub struct Extension {
     pub inner: Vec<InOutNode>,
 }
 
+impl Extension {
+    /// Returns this extension traversed in the opposite direction, i.e., from `end` to `start`.
+    /// The in- and out-nodes of all components are swapped accordingly.
+    pub fn reverse(&self) -> Extension {
+        Extension {
+            start: self.end,
+            start_out: self.end_in,
+            end: self.start,
+            end_in: self.start_out,
+            inner: self
+                .inner
+                .iter()
+                .rev()
+                .map(|node| InOutNode {
+                    in_node: node.out_node,
+                    idx: node.idx,
+                    out_node: node.in_node,
+                })
+                .collect_vec(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct InOutNode {
     pub in_node: Node,
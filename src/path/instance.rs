@@ -5,13 +5,16 @@ use itertools::Itertools;
 use crate::{
     comps::{CompType, Component},
     logic::InstanceTrait,
+    proof_tree::NodeLimit,
     types::Edge,
-    Credit, CreditInv, Node,
+    CreditInv, Node,
 };
 
 use super::{
-    extension::Extension, pseudo_cycle::PseudoCycle, EdgeId, HalfAbstractEdge, NicePairConfig,
-    PathComp, Pidx,
+    extension::Extension,
+    pseudo_cycle::PseudoCycle,
+    tactics::{is_fast_longer_path, Tactic},
+    EdgeId, HalfAbstractEdge, NicePairConfig, PathComp, Pidx,
 };
 
 #[derive(Clone, Debug)]
@@ -26,6 +29,8 @@ pub struct InstPart {
     pub contractability_checked: Vec<Pidx>,
     pub good_edges: Vec<Edge>,
     pub good_out: Vec<Node>,
+    /// Set by the mapping to the finite case, after which the path does not continue beyond its start
+    pub finite: bool,
 }
 
 impl InstPart {
@@ -41,6 +46,7 @@ impl InstPart {
             contractability_checked: vec![],
             good_edges: vec![],
             good_out: vec![],
+            finite: false,
         }
     }
 
@@ -55,6 +61,46 @@ impl InstPart {
             && self.contractability_checked.is_empty()
             && self.good_edges.is_empty()
             && self.good_out.is_empty()
+            && !self.finite
+    }
+
+    /// Returns true if one of the new edges of this part is already known to be good, or, if this part has no new
+    /// edges, one of its new outside edges. Such parts do not have to be enumerated again.
+    pub fn is_subsumed_by_good_edges(&self, good_edges: &[Edge], good_out: &[Node]) -> bool {
+        if !self.edges.is_empty() {
+            self.edges.iter().any(|edge| good_edges.contains(edge))
+        } else {
+            self.out_edges.iter().any(|node| good_out.contains(node))
+        }
+    }
+
+    /// Panics if this part contains an edge or an outside edge more than once. Duplicates would be counted
+    /// twice when computing the credits of an instance.
+    pub fn assert_no_duplicate_edges(&self) {
+        for (i, edge) in self.edges.iter().enumerate() {
+            assert!(
+                !self.edges[i + 1..].contains(edge),
+                "duplicate edge {} in instance part",
+                edge
+            );
+        }
+        for (i, node) in self.out_edges.iter().enumerate() {
+            assert!(
+                !self.out_edges[i + 1..].contains(node),
+                "duplicate outside edge at {} in instance part",
+                node
+            );
+        }
+    }
+
+    /// Returns true if this part adds at least one component to the path
+    pub fn has_new_component(&self) -> bool {
+        !self.path_nodes.is_empty()
+    }
+
+    /// Returns true if this part only adds edges, nice pairs etc. to the existing components
+    pub fn is_edge_only(&self) -> bool {
+        !self.has_new_component()
     }
 
     pub fn new_path_comp(path_comp: PathComp) -> InstPart {
@@ -69,6 +115,7 @@ impl InstPart {
             contractability_checked: vec![],
             good_edges: vec![],
             good_out: vec![],
+            finite: false,
         }
     }
 
@@ -84,6 +131,7 @@ impl InstPart {
             contractability_checked: vec![],
             good_edges: vec![],
             good_out: vec![],
+            finite: false,
         }
     }
 }
@@ -158,14 +206,29 @@ impl InstanceTrait for Instance {
 
     fn push(&mut self, ele: StackElement) {
         self.stack.push(ele);
+        if let Some(max_stack_depth) = self.context.max_stack_depth {
+            debug_assert!(
+                self.stack_depth() <= max_stack_depth,
+                "stack depth exceeds {}, some tactic or enumerator does not pop",
+                max_stack_depth
+            );
+        }
     }
 
     fn pop(&mut self) {
         self.stack.pop().unwrap();
     }
+
+    fn node_limit(&self) -> Option<&NodeLimit> {
+        self.context.node_limit.as_ref()
+    }
 }
 
 impl Instance {
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
     pub fn top_mut(&mut self) -> Option<&mut InstPart> {
         self.stack.last_mut().and_then(|last| match last {
             StackElement::Inst(part) => Some(part),
@@ -174,6 +237,20 @@ impl Instance {
         })
     }
 
+    /// Pushes `part`, evaluates `f` on the resulting instance and pops `part` again. Returns the
+    /// unchanged instance together with the result of `f`.
+    #[cfg(test)]
+    pub fn apply_part<T, F: FnOnce(&Instance) -> T>(
+        mut self,
+        part: StackElement,
+        f: F,
+    ) -> (Self, T) {
+        self.push(part);
+        let result = f(&self);
+        self.pop();
+        (self, result)
+    }
+
     pub fn inst_parts(&self) -> impl Iterator<Item = &'_ InstPart> {
         self.stack.iter().flat_map(|ele| ele.as_inst_part())
     }
@@ -190,20 +267,29 @@ impl Instance {
             .collect_vec()
     }
 
+    /// Returns the nice pairs required by the nice path definition, i.e., the in- and out-nodes of every C4 and
+    /// of an unused prelast C5.
+    pub fn required_nice_pairs(&self) -> Vec<(Node, Node)> {
+        self.path_nodes()
+            .filter(|c| c.comp.is_c4() || (c.comp.is_c5() && !c.used && c.path_idx.is_prelast()))
+            .filter_map(|c| Some((c.in_node?, c.out_node?)))
+            .collect_vec()
+    }
+
     pub fn npc(&self) -> NicePairConfig {
         // TODO
-        let nice_pairs = self
-            .inst_parts()
-            .flat_map(|part| {
+        self.inst_parts()
+            .fold(NicePairConfig::empty(), |npc, part| {
+                // adjacent nodes are always nice pairs, so we add the component edges explicitly
                 let initial_nps = part
                     .path_nodes
                     .iter()
-                    .flat_map(|c| c.initial_nps.clone())
+                    .flat_map(|c| [c.comp.edges(), c.initial_nps_nonredundant()].concat())
                     .collect_vec();
-                [initial_nps, part.nice_pairs.clone()].concat()
+                npc.union(NicePairConfig {
+                    nice_pairs: [initial_nps, part.nice_pairs.clone()].concat(),
+                })
             })
-            .collect_vec();
-        NicePairConfig { nice_pairs }
         // if let Some(part) = self
         //     .inst_parts()
         //     .filter(|part| !part.nice_pairs.is_empty())
@@ -254,9 +340,9 @@ impl Instance {
         let nodes = self.path_nodes().collect_vec();
         for w in nodes.windows(2) {
             implied_edges.push(Edge::new(
-                w[0].in_node.unwrap(),
+                w[0].in_node_or_panic("implied edges"),
                 w[0].path_idx,
-                w[1].out_node.unwrap(),
+                w[1].out_node_or_panic("implied edges"),
                 w[1].path_idx,
             ));
         }
@@ -308,6 +394,14 @@ impl Instance {
             .collect_vec()
     }
 
+    /// Returns the edges to the remaining path which start at the component with index `idx`.
+    pub fn rem_edges_from(&self, idx: Pidx) -> Vec<HalfAbstractEdge> {
+        self.rem_edges()
+            .into_iter()
+            .filter(|e| e.source_idx == idx)
+            .collect_vec()
+    }
+
     pub fn all_rem_edges(&self) -> Vec<HalfAbstractEdge> {
         self.inst_parts()
             .flat_map(|part| part.rem_edges.iter())
@@ -379,6 +473,61 @@ impl Instance {
         self.inst_parts().flat_map(|part| part.path_nodes.iter())
     }
 
+    /// Returns the component at `Pidx::Last`, i.e., the most recently added component
+    pub fn first_comp(&self) -> Option<&PathComp> {
+        self.comp_at(Pidx::Last)
+    }
+
+    /// Returns the component with the maximum path index, i.e., the start of the path
+    pub fn last_comp(&self) -> Option<&PathComp> {
+        self.path_nodes().max_by_key(|c| c.path_idx)
+    }
+
+    /// Prepends `comp` to the path, i.e., `comp` gets the index after the current start of the path
+    /// (or `Pidx::Last` for an empty path). Returns `self` for chaining.
+    pub fn extend_with_comp(&mut self, mut comp: PathComp) -> &mut Self {
+        comp.path_idx = self
+            .last_comp()
+            .map_or(Pidx::Last, |last| last.path_idx.prec());
+        self.push(StackElement::Inst(InstPart::new_path_comp(comp)));
+        self
+    }
+
+    pub fn comp_at(&self, idx: Pidx) -> Option<&PathComp> {
+        self.path_nodes().find(|c| c.path_idx == idx)
+    }
+
+    /// Returns the path index of the component which contains `n`
+    pub fn index_of_super_node(&self, n: Node) -> Pidx {
+        self.path_nodes()
+            .find(|c| c.comp.contains(&n))
+            .map(|c| c.path_idx)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Node {} is not contained in any path component of {}",
+                    n, self
+                )
+            })
+    }
+
+    /// Returns all ordered pairs of distinct components of the nice path.
+    pub fn all_comp_pairs(&self) -> impl Iterator<Item = (&'_ PathComp, &'_ PathComp)> {
+        self.path_nodes()
+            .cartesian_product(self.path_nodes().collect_vec())
+            .filter(|(c1, c2)| c1.path_idx != c2.path_idx)
+    }
+
+    /// The number of components in the nice path
+    pub fn path_len(&self) -> usize {
+        self.path_nodes().count()
+    }
+
+    /// Returns true if this instance was mapped to the finite case, i.e., the component with the largest path
+    /// index is the start of the nice path and there is no remaining path to which edges can go.
+    pub fn is_finite(&self) -> bool {
+        self.inst_parts().any(|part| part.finite)
+    }
+
     pub fn all_nodes(&self) -> impl Iterator<Item = &'_ Node> {
         self.inst_parts()
             .flat_map(|part| part.path_nodes.iter())
@@ -389,6 +538,31 @@ impl Instance {
         self.inst_parts()
             .flat_map(|part| part.contractability_checked.iter())
     }
+
+    pub fn is_contractability_checked(&self, idx: Pidx) -> bool {
+        self.contractability_checked()
+            .any(|checked| *checked == idx)
+    }
+
+    /// Cheap check whether pushing `part` onto this instance gives progress, before running all tactics.
+    /// Returns `Some(true)` if `FastLongerPath` is one of the tactics of the context and succeeds because of an
+    /// outside edge of `part`, and `None` if the full tactics are required.
+    pub fn is_progress_provable_fast(&self, part: &InstPart) -> Option<bool> {
+        let fast_longer_path = self
+            .context
+            .tactic_ordering
+            .iter()
+            .any(|tactic| matches!(tactic, Tactic::FastLongerPath(_)));
+        if fast_longer_path
+            && self
+                .first_comp()
+                .is_some_and(|last| is_fast_longer_path(last, &part.out_edges))
+        {
+            return Some(true);
+        }
+
+        None
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -455,6 +629,21 @@ impl PathNode {
         matches!(self, Self::Used(_))
     }
 
+    pub fn set_used(&mut self, used: bool) {
+        let comp = self.get_comp().clone();
+        *self = if used {
+            PathNode::Used(comp)
+        } else {
+            PathNode::Unused(comp)
+        };
+    }
+
+    pub fn into_used(self) -> PathNode {
+        match self {
+            PathNode::Used(c) | PathNode::Unused(c) => PathNode::Used(c),
+        }
+    }
+
     pub fn get_comp(&self) -> &Component {
         match self {
             PathNode::Used(c) | PathNode::Unused(c) => c,
@@ -473,6 +662,67 @@ impl PathNode {
 pub struct InstanceContext {
     pub inv: CreditInv,
     pub comps: Vec<PathNode>,
+    pub node_limit: Option<NodeLimit>,
+    /// If set, pushing more elements onto the stack of an instance panics.
+    pub max_stack_depth: Option<usize>,
+    /// If set, necessary conditions of every successful tactic are checked by an independent verifier.
+    pub verify_mode: bool,
+    /// If set, the edge enumerator tries the simple case check first, see `GreedyEvalConfig::with_sc`.
+    pub simple_case: bool,
+    /// The tactics which `check_progress` tries when computing good edges.
+    pub tactic_ordering: Vec<Tactic>,
+}
+
+impl InstanceContext {
+    pub fn new(inv: CreditInv, comps: Vec<PathNode>) -> Self {
+        InstanceContext {
+            inv,
+            comps,
+            node_limit: None,
+            max_stack_depth: None,
+            verify_mode: false,
+            simple_case: false,
+            tactic_ordering: Tactic::default_ordering(),
+        }
+    }
+
+    pub fn builder() -> InstanceContextBuilder {
+        InstanceContextBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InstanceContextBuilder {
+    inv: Option<CreditInv>,
+    comps: Vec<PathNode>,
+    node_limit: Option<NodeLimit>,
+}
+
+impl InstanceContextBuilder {
+    pub fn inv(mut self, inv: CreditInv) -> Self {
+        self.inv = Some(inv);
+        self
+    }
+
+    pub fn add_comp(mut self, comp: PathNode) -> Self {
+        self.comps.push(comp);
+        self
+    }
+
+    pub fn node_limit(mut self, node_limit: Option<NodeLimit>) -> Self {
+        self.node_limit = node_limit;
+        self
+    }
+
+    pub fn build(self) -> InstanceContext {
+        let mut context = InstanceContext::new(
+            self.inv
+                .expect("InstanceContext requires a credit invariant"),
+            self.comps,
+        );
+        context.node_limit = self.node_limit;
+        context
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -500,3 +750,38 @@ impl Display for InstanceProfile {
         write!(f, "{}", self.comp_types.iter().join("--"))
     }
 }
+
+#[cfg(test)]
+mod test_instance {
+    use super::*;
+    use crate::{comps::c5, Credit};
+
+    #[test]
+    fn test_apply_part() {
+        let comp = c5();
+        let mut instance = Instance {
+            stack: vec![],
+            context: InstanceContext::builder()
+                .inv(CreditInv::new(Credit::new(2, 7)))
+                .add_comp(PathNode::Unused(comp.clone()))
+                .build(),
+        };
+        instance.extend_with_comp(PathComp {
+            in_node: Some(comp.fixed_node()),
+            out_node: None,
+            initial_nps: comp.edges(),
+            comp,
+            used: false,
+            path_idx: Pidx::Last,
+        });
+
+        let mut part = InstPart::empty();
+        part.out_edges = vec![Node::n(1)];
+        let (instance, out_edges) =
+            instance.apply_part(StackElement::Inst(part), |instance| instance.out_edges());
+
+        assert_eq!(out_edges, vec![Node::n(1)]);
+        assert!(instance.out_edges().is_empty());
+        assert_eq!(instance.stack_depth(), 1);
+    }
+}
@@ -9,8 +9,10 @@ mod tactics;
 use std::{cmp::Ordering, fmt::Display};
 
 use itertools::Itertools;
+pub use proof::print_enumeration_plan;
 pub use proof::prove_nice_path_progress;
 pub use proof::PathProofOptions;
+pub use tactics::Tactic;
 
 use crate::proof_tree::ProofNode;
 use crate::Credit;
@@ -74,9 +76,8 @@ impl Display for PathComp {
                 out_n,
                 self.path_idx,
                 self.used,
-                self.initial_nps
+                self.initial_nps_nonredundant()
                     .iter()
-                    .filter(|(u, v)| !self.comp.is_adjacent(u, v))
                     .map(|(u, v)| format!("({},{})", u, v))
                     .join(",")
             ),
@@ -84,6 +85,32 @@ impl Display for PathComp {
     }
 }
 
+impl PathComp {
+    /// The initial nice pairs without the pairs which are adjacent in the component. Adjacent pairs are always nice,
+    /// so only the remaining pairs carry information.
+    pub fn initial_nps_nonredundant(&self) -> Vec<(Node, Node)> {
+        self.initial_nps
+            .iter()
+            .filter(|(u, v)| !self.comp.is_adjacent(u, v))
+            .cloned()
+            .collect_vec()
+    }
+
+    /// Returns the in node of this component. Panics with a message naming the component and `context`, i.e. the
+    /// proof step which requires the in node, if there is none.
+    pub fn in_node_or_panic(&self, context: &str) -> Node {
+        self.in_node
+            .unwrap_or_else(|| panic!("{}: component {} has no in node", context, self))
+    }
+
+    /// Returns the out node of this component. Panics with a message naming the component and `context`, i.e. the
+    /// proof step which requires the out node, if there is none.
+    pub fn out_node_or_panic(&self, context: &str) -> Node {
+        self.out_node
+            .unwrap_or_else(|| panic!("{}: component {} has no out node", context, self))
+    }
+}
+
 impl PartialEq for PathComp {
     fn eq(&self, other: &Self) -> bool {
         self.path_idx == other.path_idx
@@ -113,6 +140,48 @@ pub struct HalfAbstractEdge {
     pub matching: bool,
 }
 
+impl HalfAbstractEdge {
+    /// Returns a builder for an edge from `source` in component `source_idx` to the remaining path.
+    /// By default, the edge has id 0, cost 1 and is not a matching edge.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(source: Node, source_idx: Pidx) -> HalfAbstractEdgeBuilder {
+        HalfAbstractEdgeBuilder {
+            edge: HalfAbstractEdge {
+                source,
+                source_idx,
+                id: EdgeId(0),
+                cost: Credit::from_integer(1),
+                matching: false,
+            },
+        }
+    }
+}
+
+pub struct HalfAbstractEdgeBuilder {
+    edge: HalfAbstractEdge,
+}
+
+impl HalfAbstractEdgeBuilder {
+    pub fn id(mut self, id: EdgeId) -> Self {
+        self.edge.id = id;
+        self
+    }
+
+    pub fn cost(mut self, cost: Credit) -> Self {
+        self.edge.cost = cost;
+        self
+    }
+
+    pub fn matching(mut self, matching: bool) -> Self {
+        self.edge.matching = matching;
+        self
+    }
+
+    pub fn build(self) -> HalfAbstractEdge {
+        self.edge
+    }
+}
+
 impl Display for HalfAbstractEdge {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}-REM(c={},id={})", self.source, self.cost, self.id)
@@ -140,16 +209,41 @@ impl Display for NicePairConfig {
 }
 
 impl NicePairConfig {
+    pub fn empty() -> Self {
+        NicePairConfig { nice_pairs: vec![] }
+    }
+
+    /// Returns all nice pairs of both configurations. Pairs already contained in `self` are not added again.
+    pub fn union(mut self, other: NicePairConfig) -> NicePairConfig {
+        for (u, v) in other.nice_pairs {
+            if !self.is_nice_pair(u, v) {
+                self.nice_pairs.push((u, v));
+            }
+        }
+        self
+    }
+
+    /// Returns all nice pairs which are nice in both configurations.
+    #[cfg(test)]
+    pub fn intersection(self, other: &NicePairConfig) -> NicePairConfig {
+        let nice_pairs = self
+            .nice_pairs
+            .into_iter()
+            .filter(|(u, v)| other.is_nice_pair(*u, *v))
+            .collect_vec();
+        NicePairConfig { nice_pairs }
+    }
+
     pub fn is_nice_pair(&self, u: Node, v: Node) -> bool {
         self.nice_pairs
             .iter()
             .any(|(a, b)| (*a == u && *b == v) || (*a == v && *b == u))
     }
 
-    // Checks whether this configuration is consistent with `consistent_npc` on the node set `consistent_nodes`.
-    // This function returns true if for every pair of nodes from `consistent_nodes`, this configuration has the
-    // same value for this pair as `consistent_npc`.
-    #[allow(dead_code)]
+    /// Checks whether this configuration is consistent with `consistent_npc` on the node set `consistent_nodes`.
+    /// This function returns true if for every pair of nodes from `consistent_nodes`, this configuration has the
+    /// same value for this pair as `consistent_npc`.
+    #[cfg(test)]
     pub fn is_consistent_with(
         &self,
         consistent_npc: &NicePairConfig,
@@ -178,6 +272,12 @@ impl Pidx {
         (0..len).map(Pidx::from).collect_vec()
     }
 
+    /// Returns the indices of all components of a path with `len` components from the start of the path down to the
+    /// last component, i.e. `[N(len - 1), ..., N(2), Prelast, Last]`.
+    pub fn all_up_to(len: usize) -> Vec<Pidx> {
+        (0..len).rev().map(Pidx::from).collect_vec()
+    }
+
     pub fn raw(&self) -> usize {
         match self {
             Pidx::Last => 0,
@@ -186,6 +286,15 @@ impl Pidx {
         }
     }
 
+    /// Returns a label which can be used in file names and proofs, i.e. `last`, `prelast` or `p{n}`.
+    pub fn to_label(self) -> String {
+        match self {
+            Pidx::Last => "last".to_string(),
+            Pidx::Prelast => "prelast".to_string(),
+            Pidx::N(n) => format!("p{}", n),
+        }
+    }
+
     pub fn prec(&self) -> Pidx {
         if let Pidx::Last = self {
             Pidx::Prelast
@@ -222,7 +331,7 @@ impl From<usize> for Pidx {
 
 impl PartialOrd for Pidx {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.raw().partial_cmp(&other.raw())
+        Some(self.cmp(other))
     }
 }
 
@@ -249,3 +358,74 @@ impl Display for Pidx {
         }
     }
 }
+
+#[cfg(test)]
+mod test_nice_pair_config {
+    use super::*;
+
+    fn npc(pairs: &[(u32, u32)]) -> NicePairConfig {
+        NicePairConfig {
+            nice_pairs: pairs
+                .iter()
+                .map(|(u, v)| (Node::n(*u), Node::n(*v)))
+                .collect(),
+        }
+    }
+
+    fn nodes(ids: &[u32]) -> Vec<Node> {
+        ids.iter().map(|u| Node::n(*u)).collect()
+    }
+
+    #[test]
+    fn test_consistent() {
+        // pairs are unordered, and pairs outside of the node set are ignored
+        let npc1 = npc(&[(0, 1), (1, 2), (3, 4)]);
+        let npc2 = npc(&[(2, 1), (1, 0)]);
+        assert!(npc1.is_consistent_with(&npc2, &nodes(&[0, 1, 2])));
+        assert!(npc2.is_consistent_with(&npc1, &nodes(&[0, 1, 2])));
+    }
+
+    #[test]
+    fn test_inconsistent() {
+        let npc1 = npc(&[(0, 1), (1, 2)]);
+        let npc2 = npc(&[(0, 1)]);
+        assert!(!npc1.is_consistent_with(&npc2, &nodes(&[0, 1, 2])));
+        assert!(!npc2.is_consistent_with(&npc1, &nodes(&[0, 1, 2])));
+    }
+
+    #[test]
+    fn test_empty_nodes() {
+        let npc1 = npc(&[(0, 1)]);
+        let npc2 = npc(&[]);
+        assert!(npc1.is_consistent_with(&npc2, &[]));
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let npc1 = npc(&[(0, 1), (1, 2)]);
+        let npc2 = npc(&[(2, 1), (2, 3)]);
+
+        let union = npc1.clone().union(npc2.clone());
+        assert_eq!(union.nice_pairs.len(), 3);
+        assert!(union.is_consistent_with(&npc(&[(0, 1), (1, 2), (2, 3)]), &nodes(&[0, 1, 2, 3])));
+
+        let intersection = npc1.intersection(&npc2);
+        assert_eq!(intersection.nice_pairs.len(), 1);
+        assert!(intersection.is_nice_pair(Node::n(2), Node::n(1)));
+    }
+}
+
+#[cfg(test)]
+mod test_pidx {
+    use super::*;
+
+    #[test]
+    fn test_all_up_to() {
+        let indices = Pidx::all_up_to(4);
+        assert_eq!(
+            indices,
+            vec![Pidx::N(3), Pidx::N(2), Pidx::Prelast, Pidx::Last]
+        );
+        assert!(Pidx::all_up_to(0).is_empty());
+    }
+}
@@ -30,3 +30,85 @@ pub fn valid_in_out_pre_npc(c: &Component, new_in: Node, new_out: Node, prelast:
         true
     }
 }
+
+#[cfg(test)]
+mod test_valid_in_out_npc {
+    use super::*;
+    use crate::comps::{c4, c5, c6, c7, large};
+
+    fn npc(pairs: &[(u32, u32)]) -> NicePairConfig {
+        NicePairConfig {
+            nice_pairs: pairs.iter().map(|(u, v)| (n(*u), n(*v))).collect(),
+        }
+    }
+
+    fn n(u: u32) -> Node {
+        Node::n(u)
+    }
+
+    // Evaluates all (prelast, used) combinations in the order
+    // (false, false), (false, true), (true, false), (true, true).
+    fn all_flags(c: &Component, npc: &NicePairConfig, new_in: Node, new_out: Node) -> [bool; 4] {
+        [
+            valid_in_out_npc(c, npc, new_in, new_out, false, false),
+            valid_in_out_npc(c, npc, new_in, new_out, false, true),
+            valid_in_out_npc(c, npc, new_in, new_out, true, false),
+            valid_in_out_npc(c, npc, new_in, new_out, true, true),
+        ]
+    }
+
+    #[test]
+    fn test_c4() {
+        let c = c4();
+        // in == out is never a nice pair
+        assert_eq!(all_flags(&c, &npc(&[]), n(0), n(0)), [false; 4]);
+        // adjacent in and out
+        assert_eq!(all_flags(&c, &npc(&[(0, 1)]), n(0), n(1)), [true; 4]);
+        assert_eq!(all_flags(&c, &npc(&[]), n(0), n(1)), [false; 4]);
+        // non-adjacent in and out
+        assert_eq!(all_flags(&c, &npc(&[(0, 2)]), n(0), n(2)), [true; 4]);
+        assert_eq!(all_flags(&c, &npc(&[(2, 0)]), n(0), n(2)), [true; 4]);
+        assert_eq!(all_flags(&c, &npc(&[(1, 3)]), n(0), n(2)), [false; 4]);
+    }
+
+    #[test]
+    fn test_c5() {
+        let c = c5();
+        // only a used prelast C5 may not have in == out
+        assert_eq!(
+            all_flags(&c, &npc(&[]), n(0), n(0)),
+            [true, true, false, false]
+        );
+        // adjacent in and out
+        assert_eq!(
+            all_flags(&c, &npc(&[(0, 1)]), n(0), n(1)),
+            [true, true, true, true]
+        );
+        assert_eq!(
+            all_flags(&c, &npc(&[]), n(0), n(1)),
+            [true, true, false, true]
+        );
+        // non-adjacent in and out
+        assert_eq!(
+            all_flags(&c, &npc(&[(0, 2)]), n(0), n(2)),
+            [true, true, true, true]
+        );
+        assert_eq!(
+            all_flags(&c, &npc(&[(1, 3)]), n(0), n(2)),
+            [true, true, false, true]
+        );
+    }
+
+    #[test]
+    fn test_c6_c7_large() {
+        for c in [c6(), c7()] {
+            assert_eq!(all_flags(&c, &npc(&[]), n(0), n(0)), [true; 4]);
+            assert_eq!(all_flags(&c, &npc(&[]), n(0), n(1)), [true; 4]);
+            assert_eq!(all_flags(&c, &npc(&[]), n(0), n(3)), [true; 4]);
+            assert_eq!(all_flags(&c, &npc(&[(0, 3)]), n(0), n(3)), [true; 4]);
+        }
+        let c = large();
+        let node = Node::c(0);
+        assert_eq!(all_flags(&c, &npc(&[]), node, node), [true; 4]);
+    }
+}
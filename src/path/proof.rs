@@ -1,12 +1,15 @@
 use chrono::prelude::*;
 use std::fmt::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use itertools::Itertools;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 use crate::path::instance::{InstanceContext, PathNode};
 use crate::path::{PathComp, PathProofNode, Pidx};
+use crate::proof_tree::{NodeLimit, Outcome};
 use crate::{comps::Component, CreditInv};
 
 use super::enumerators::{path_comp_enumerator, Enumerator, OptEnumerator};
@@ -32,6 +35,7 @@ impl MapperTrait for Mapper {
                 let mut part = InstPart::empty();
                 part.non_rem_edges.append(&mut rem_ids);
                 part.out_edges.append(&mut rem_sources);
+                part.finite = true;
 
                 StackElement::Inst(part)
             }
@@ -39,15 +43,18 @@ impl MapperTrait for Mapper {
     }
 }
 
-fn prove_progress(finite: bool, options: PathProofOptions, depth: u8) -> ProofExpr {
+fn prove_progress(finite: bool, options: &PathProofOptions, depth: u8) -> ProofExpr {
     if depth > 0 {
-        or(progress(finite), split_cases(finite, options, depth - 1))
+        or(
+            progress(finite, &options.tactic_ordering),
+            split_cases(finite, options, depth - 1),
+        )
     } else {
         expr(Tactic::TacticsExhausted(false))
     }
 }
 
-fn split_cases(finite: bool, options: PathProofOptions, depth: u8) -> ProofExpr {
+fn split_cases(finite: bool, options: &PathProofOptions, depth: u8) -> ProofExpr {
     all_opt(
         OptEnumerator::Edges(finite),
         prove_progress(finite, options, depth),
@@ -78,45 +85,83 @@ fn split_cases(finite: bool, options: PathProofOptions, depth: u8) -> ProofExpr
     )
 }
 
-fn progress(finite: bool) -> ProofExpr {
-    or5(
-        expr(Tactic::FastLongerPath(finite)),
-        expr(Tactic::LocalMerge),
-        expr(Tactic::Pendant),
-        expr(Tactic::LongerPath(finite)),
-        any(
-            Enumerator::PseudoCycle(finite),
-            or(
-                expr(Tactic::CycleMerge),
-                any(
-                    Enumerator::Rearrangments(finite),
-                    or(
-                        expr(Tactic::Rearrangable(finite)),
-                        expr(Tactic::LongerPath(finite)),
+/// Tries the tactics in the order given by `tactic_ordering`. `CycleMerge` stands for the enumeration
+/// of pseudo cycles, which are either merged or rearranged.
+fn progress(finite: bool, tactic_ordering: &[Tactic]) -> ProofExpr {
+    tactic_ordering
+        .iter()
+        .rev()
+        .map(|tactic| match tactic {
+            Tactic::CycleMerge => any(
+                Enumerator::PseudoCycle(finite),
+                or(
+                    expr(Tactic::CycleMerge),
+                    any(
+                        Enumerator::Rearrangments(finite),
+                        or(
+                            expr(Tactic::Rearrangable(finite)),
+                            expr(Tactic::LongerPath(finite)),
+                        ),
                     ),
                 ),
             ),
-        ),
-    )
+            _ => expr(tactic.with_finite(finite)),
+        })
+        .reduce(|acc, e| or(e, acc))
+        .expect("at least one tactic is required")
 }
 
 pub fn check_progress(instance: &mut Instance, finite: bool, part: InstPart) -> bool {
+    if let Some(provable) = instance.is_progress_provable_fast(&part) {
+        return provable;
+    }
+
+    let expr = progress(finite, &instance.context.tactic_ordering);
     instance.push(StackElement::Inst(part));
-    let mut proof = progress(finite).prove(instance);
+    let mut proof = expr.prove(instance);
     proof.eval();
     let outcome = proof.outcome();
     instance.pop();
     outcome.success()
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct PathProofOptions {
     pub max_depth: u8,
     pub initial_node_depth: u8,
     pub sc: bool,
+    /// The order in which the tactics are tried, see `Tactic::default_ordering`
+    pub tactic_ordering: Vec<Tactic>,
+    /// Print the distribution of leaf outcomes of the final (pruned) proof tree after each proof
+    pub stats: bool,
+    /// Check necessary conditions of every successful tactic with an independent verifier, which panics if they do
+    /// not hold
+    pub verify_mode: bool,
+    /// Enumerate a 3-matching of the last component before its type-specific edge configurations
+    pub simple_case: bool,
 }
 
-/// Starts the proof for a specific last component
+/// Checks that all components are labeled such that relabeling them sequentially, as done when the path is
+/// extended, results in disjoint node ids. This is the case if every component is valid.
+fn check_component_labels(comps: &[Component]) -> anyhow::Result<()> {
+    for comp in comps {
+        if !comp.is_valid() {
+            anyhow::bail!(
+                "Component {} has invalid node ids {:?}, expected 0..{}",
+                comp,
+                comp.nodes(),
+                comp.num_labels()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Starts the proof for a specific last component. Returns true if the proof is complete for all cases.
+/// Returns an error if the node ids of the components are invalid.
+/// If `timeout_flag` is set, the proof stops after the currently running cases and writes a partial result.
+/// If the proof tree of a last component grows beyond `max_nodes` nodes, the remaining cases fail with a "Size limit exceeded" leaf.
+#[allow(clippy::too_many_arguments)]
 pub fn prove_nice_path_progress(
     comps: Vec<Component>,
     last_comp: Component,
@@ -125,44 +170,131 @@ pub fn prove_nice_path_progress(
     output_depth: usize,
     options: PathProofOptions,
     _parallel: bool,
-) {
+    timeout_flag: Arc<AtomicBool>,
+    max_nodes: Option<usize>,
+) -> anyhow::Result<bool> {
+    check_component_labels(
+        &comps
+            .iter()
+            .chain(std::iter::once(&last_comp))
+            .cloned()
+            .collect_vec(),
+    )?;
+
     std::fs::create_dir_all(&output_dir).expect("Unable to create directory");
 
-    // Prepare proof cases
-    let nodes = comps
+    for comp in comps.iter().chain(std::iter::once(&last_comp)) {
+        assert!(
+            comp.is_supported_by_all_tactics(),
+            "Component {} is not supported by all tactics!",
+            comp
+        );
+        // components are 2-edge-connected, and the tactics rely on the nice pairs of a Hamiltonian cycle
+        assert!(
+            comp.has_hamiltonian_cycle(),
+            "Component {} has no Hamiltonian cycle!",
+            comp
+        );
+    }
+
+    let nodes = to_path_nodes(comps);
+    let proof_cases = to_path_nodes(vec![last_comp]);
+
+    let mut proved = true;
+    for last_node in proof_cases {
+        if timeout_flag.load(Ordering::SeqCst) {
+            println!(
+                "TIMEOUT: Skipping proof ending in {}",
+                last_node.short_name()
+            );
+            proved = false;
+            continue;
+        }
+        // start a separate proof for every possible last node
+        proved &= prove_last_node(
+            nodes.clone(),
+            last_node,
+            credit_inv.clone(),
+            &output_dir,
+            output_depth,
+            &options,
+            true,
+            &timeout_flag,
+            max_nodes,
+        );
+    }
+    Ok(proved)
+}
+
+/// Returns the path nodes for the given components. Isomorphic components would only produce duplicate cases and
+/// are thus skipped.
+fn to_path_nodes(comps: Vec<Component>) -> Vec<PathNode> {
+    comps
         .into_iter()
+        .unique_by(|comp| comp.to_isomorphism_class())
         .flat_map(|comp| {
             if comp.is_c5() {
-                vec![PathNode::Unused(comp.clone()), PathNode::Used(comp)] // C5 can be used or unused
+                let node = PathNode::Unused(comp);
+                vec![node.clone(), node.into_used()] // C5 can be used or unused
             } else {
                 vec![PathNode::Unused(comp)]
             }
         })
-        .collect_vec();
+        .collect_vec()
+}
 
-    let last_nodes = if last_comp.is_c5() {
-        vec![
-            PathNode::Unused(last_comp.clone()),
-            PathNode::Used(last_comp),
-        ]
-    } else {
-        vec![PathNode::Unused(last_comp)]
-    };
+/// The maximum number of stack elements of an instance during a proof with `options`. The initial case has one
+/// element per initial component. Every level of the proof pushes one case, either of the edge or of the path node
+/// enumeration, and the finite case pushes its mapping once. On top of that, `check_progress` pushes a part, a
+/// pseudo cycle and a rearrangement.
+fn max_stack_depth(options: &PathProofOptions) -> usize {
+    options.initial_node_depth as usize + options.max_depth as usize + 1 + 3
+}
 
-    let proof_cases = last_nodes;
+fn prove_case(case: &mut Instance, options: &PathProofOptions) -> PathProofNode {
+    case.context.max_stack_depth = Some(max_stack_depth(options));
+    case.context.verify_mode = options.verify_mode;
+    case.context.simple_case = options.simple_case;
+    case.context.tactic_ordering = options.tactic_ordering.clone();
 
-    proof_cases.into_iter().for_each(|last_node| {
-        // start a separate proof for every possible last node
-        prove_last_node(
-            nodes.clone(),
-            last_node,
-            credit_inv.clone(),
-            &output_dir,
-            output_depth,
-            options,
-            true,
-        )
-    })
+    // build the expression tree statically
+    let expr = prove_progress(false, options, options.max_depth);
+
+    // evaluate the expression tree
+    expr.prove(case)
+}
+
+/// Prints an estimate of the number of cases at every depth of the path enumeration, without proving anything.
+/// Every new component multiplies the number of cases by the number of its possible in nodes, summed over all
+/// component types.
+pub fn print_enumeration_plan(
+    comps: Vec<Component>,
+    last_comp: Component,
+    options: &PathProofOptions,
+) {
+    let nodes = to_path_nodes(comps);
+    let last_nodes = to_path_nodes(vec![last_comp]);
+
+    let cases_per_comp = nodes
+        .iter()
+        .map(|node| node.get_comp().in_nodes().len() as u128)
+        .sum::<u128>();
+
+    println!(
+        "{} last components, {} cases per additional component",
+        last_nodes.len(),
+        cases_per_comp
+    );
+    let mut cases = last_nodes.len() as u128;
+    for depth in 1..=(options.initial_node_depth as usize + options.max_depth as usize) {
+        let marker = if depth == options.initial_node_depth as usize {
+            " (initial cases)"
+        } else {
+            ""
+        };
+        println!("depth {}: {} cases{}", depth, cases, marker);
+        cases = cases.saturating_mul(cases_per_comp);
+    }
 }
 
 fn compute_initial_cases(
@@ -170,6 +302,7 @@ fn compute_initial_cases(
     last_node: PathNode,
     mut depth: u8,
     credit_inv: CreditInv,
+    max_nodes: Option<usize>,
 ) -> Vec<Instance> {
     let comp = last_node.get_comp().clone(); // last component
     let in_node = comp.fixed_node();
@@ -185,14 +318,20 @@ fn compute_initial_cases(
     };
 
     // the initial case only contains the last component
+    let context = nodes
+        .iter()
+        .fold(
+            InstanceContext::builder().inv(credit_inv.clone()),
+            |builder, node| builder.add_comp(node.clone()),
+        )
+        // the limit is shared by all cases, since cloned instances share the counter
+        .node_limit(max_nodes.map(NodeLimit::new))
+        .build();
     let mut initial_case = Instance {
         stack: vec![],
-        context: InstanceContext {
-            inv: credit_inv.clone(),
-            comps: nodes.clone(),
-        },
+        context,
     };
-    initial_case.push(StackElement::Inst(InstPart::new_path_comp(path_comp)));
+    initial_case.extend_with_comp(path_comp);
 
     // expand cases based on given depth
     let mut cases = vec![initial_case];
@@ -215,22 +354,27 @@ fn compute_initial_cases(
     cases
 }
 
+#[allow(clippy::too_many_arguments)]
 fn prove_last_node(
     nodes: Vec<PathNode>,
     last_node: PathNode,
     credit_inv: CreditInv,
-    output_dir: &PathBuf,
+    output_dir: &Path,
     output_depth: usize,
-    options: PathProofOptions,
+    options: &PathProofOptions,
     _parallel: bool,
-) {
+    timeout_flag: &AtomicBool,
+    max_nodes: Option<usize>,
+) -> bool {
     let cases = compute_initial_cases(
         nodes,
         last_node.clone(),
         options.initial_node_depth,
         credit_inv.clone(),
+        max_nodes,
     );
-    println!("{} cases to check!", cases.len());
+    let num_cases = cases.len();
+    println!("{} cases to check!", num_cases);
 
     for case in &cases {
         let profile = case.get_profile(false);
@@ -242,11 +386,12 @@ fn prove_last_node(
     let proofs: Vec<PathProofNode> = cases
         .into_par_iter()
         .map(|mut case| {
-            // build the expression tree statically
-            let expr = prove_progress(false, options, options.max_depth);
+            if timeout_flag.load(Ordering::SeqCst) {
+                // do not start new cases after a timeout
+                return PathProofNode::new_leaf(format!("TIMEOUT: {}", case), false);
+            }
 
-            // evaluate the expression tree
-            let mut proof = expr.prove(&mut case);
+            let mut proof = prove_case(&mut case, options);
 
             // get the outcome
             let outcome = proof.eval();
@@ -257,7 +402,14 @@ fn prove_last_node(
                 println!("[{}] ✔️ Proved case {}: {}", local, profile, case);
             } else {
                 println!("[{}] ❌ Disproved case {}: {}", local, profile, case);
-                let buf = proof_to_string(&proof, output_depth, &credit_inv);
+                if let Some(msgs) = proof.info_messages_to_failing_leaf() {
+                    println!("First failing subcase: {}", msgs.join(" -> "));
+                }
+                // only log the branches which lead to failing leaves. An empty enumeration fails without such a leaf.
+                let failing = proof
+                    .retain_path_to_outcome(Outcome::False)
+                    .unwrap_or_else(|| proof.clone());
+                let buf = proof_to_string(&failing, output_depth, &credit_inv);
                 log::info!("{}", buf);
             };
 
@@ -271,26 +423,73 @@ fn prove_last_node(
 
     total_proof.eval();
     let outcome = total_proof.outcome();
-    //print_path_statistics(&total_proof);
-    let filename = if outcome.success() {
+    if options.stats {
+        print_outcome_statistics(&total_proof);
+    }
+    let filename = if timeout_flag.load(Ordering::SeqCst) {
+        println!(
+            "TIMEOUT: Partial proof of nice path progress ending in {}",
+            last_node.short_name(),
+        );
+        output_dir.join(format!(
+            "timeout_proof_{}_{}.txt",
+            last_node.short_name(),
+            credit_inv.to_filename_suffix()
+        ))
+    } else if outcome.success() {
         println!(
             "✔️ Proved nice path progress ending in {}",
             last_node.short_name(),
         );
-        output_dir.join(format!("proof_{}.txt", last_node.short_name(),))
+        output_dir.join(format!(
+            "proof_{}_{}.txt",
+            last_node.short_name(),
+            credit_inv.to_filename_suffix()
+        ))
     } else {
         println!(
             "❌ Disproved nice path progress ending in {}",
             last_node.short_name(),
         );
-        output_dir.join(format!("wrong_proof_{}.txt", last_node.short_name(),))
+        println!(
+            "{} of {} cases failed",
+            total_proof.get_failing_children().len(),
+            num_cases
+        );
+        output_dir.join(format!(
+            "wrong_proof_{}_{}.txt",
+            last_node.short_name(),
+            credit_inv.to_filename_suffix()
+        ))
     };
 
     println!();
     println!();
 
-    let buf = proof_to_string(&total_proof, output_depth, &credit_inv);
+    let mut buf = proof_to_string(&total_proof, output_depth, &credit_inv);
+    let timeout = timeout_flag.load(Ordering::SeqCst);
+    if timeout {
+        buf.insert_str(0, "TIMEOUT\n");
+    }
     std::fs::write(filename, buf).expect("Unable to write file");
+
+    outcome.success() && !timeout
+}
+
+fn print_outcome_statistics(proof: &PathProofNode) {
+    let histogram = proof.outcome_histogram();
+    let total = histogram.values().sum::<usize>().max(1);
+    // successful subtrees of enumerations are pruned during the proof, so these are the leaves of the final proof tree
+    println!("Leaf outcomes:");
+    for outcome in [Outcome::True, Outcome::Tight, Outcome::False] {
+        let count = histogram.get(&outcome).cloned().unwrap_or(0);
+        println!(
+            "  {:?}: {} ({:.1}%)",
+            outcome,
+            count,
+            100.0 * count as f64 / total as f64
+        );
+    }
 }
 
 fn proof_to_string(proof: &PathProofNode, output_depth: usize, credit_inv: &CreditInv) -> String {
@@ -306,3 +505,38 @@ fn proof_to_string(proof: &PathProofNode, output_depth: usize, credit_inv: &Cred
         .expect("Unable to format tree");
     buf
 }
+
+#[cfg(test)]
+mod test_proof {
+    use super::*;
+    use crate::{
+        comps::{c4, c5, c6, large},
+        Credit,
+    };
+
+    #[test]
+    fn test_stack_depth_at_default_max_depth() {
+        let options = PathProofOptions {
+            max_depth: 20,
+            initial_node_depth: 2,
+            sc: false,
+            tactic_ordering: Tactic::default_ordering(),
+            stats: false,
+            verify_mode: false,
+            simple_case: false,
+        };
+        let nodes = to_path_nodes(vec![c4(), c5(), c6(), large()]);
+        let cases = compute_initial_cases(
+            nodes,
+            PathNode::Unused(large()),
+            options.initial_node_depth,
+            CreditInv::new(Credit::new(2, 7)),
+            None,
+        );
+        // a single case, since every case takes a few seconds in debug builds
+        let mut case = cases.into_iter().next().unwrap();
+        // pushing beyond max_stack_depth panics
+        let mut proof = prove_case(&mut case, &options);
+        assert!(proof.eval().success());
+    }
+}
@@ -4,7 +4,7 @@ use itertools::Itertools;
 
 use crate::{Credit, Node};
 
-use super::Pidx;
+use super::{PathComp, Pidx};
 
 #[derive(Clone, Debug)]
 pub struct PseudoCycle {
@@ -13,6 +13,41 @@ pub struct PseudoCycle {
 }
 
 impl PseudoCycle {
+    /// Creates a new pseudo cycle. Panics if the cycle has less than two components or if the
+    /// in- and out-nodes of a component in the cycle are not nodes of that component in `path_comps`.
+    pub fn new(
+        cycle: Vec<(Node, CycleComp, Node)>,
+        total_edge_cost: Credit,
+        path_comps: &[PathComp],
+    ) -> Self {
+        assert!(
+            cycle.len() >= 2,
+            "pseudo cycle must have at least two components"
+        );
+        for (in_node, cycle_comp, out_node) in &cycle {
+            if let CycleComp::PathComp(idx) = cycle_comp {
+                let comp = &path_comps[idx.raw()].comp;
+                assert!(
+                    comp.contains(in_node) && comp.contains(out_node),
+                    "nodes {} and {} are not contained in component {}",
+                    in_node,
+                    out_node,
+                    idx
+                );
+            }
+        }
+
+        PseudoCycle {
+            cycle,
+            total_edge_cost,
+        }
+    }
+
+    /// Returns the number of components in this cycle, including `Rem`
+    pub fn cycle_length(&self) -> usize {
+        self.cycle.len()
+    }
+
     pub fn consecutive_end(&self) -> bool {
         let mut indices = self
             .cycle
@@ -40,7 +75,7 @@ impl Display for PseudoCycle {
                 CycleComp::Rem => "REM".to_string(),
             })
             .join(", ");
-        write!(f, "PC [ {} ] (length={})", inner, self.cycle.len())
+        write!(f, "PC [ {} ] (length={})", inner, self.cycle_length())
     }
 }
 
@@ -16,6 +16,13 @@ use crate::{
 /// Tactic which tries to merge the current pseudo cycle
 pub fn check_cycle_merge(instance: &Instance) -> PathProofNode {
     let pc = instance.pseudo_cycle().unwrap();
+    if pc.cycle.iter().all(|(_, c, _)| matches!(c, CycleComp::Rem)) {
+        return PathProofNode::new_leaf(
+            "Pseudo cycle only consists of the remaining path".into(),
+            false,
+        );
+    }
+
     let all_edges = instance.all_inter_comp_edges();
     let path_comps = instance.path_nodes().collect_vec();
     let npc = instance.npc();
@@ -28,6 +35,15 @@ pub fn check_cycle_merge(instance: &Instance) -> PathProofNode {
             true,
         )
     } else {
+        log::debug!(
+            "Failed cycle merge of {} with value {}, component values: {}",
+            pc,
+            cycle_value,
+            pc.all_cycle_values(&path_comps, &all_edges, &npc, instance)
+                .iter()
+                .map(|(idx, value)| format!("{}: {}", idx, value))
+                .join(", ")
+        );
         PathProofNode::new_leaf(
             format!(
                 "Failed cycle merge with value {}",
@@ -54,6 +70,29 @@ impl CompValue {
     }
 }
 
+/// Returns all pairs of edges between `comp` and another component which is not part of the cycle, together with this
+/// other component. Such a pair allows a local merge with the other component as a shortcut of the cycle.
+fn edge_pairs_to_other_comps<'a>(
+    instance: &'a Instance,
+    comp: &PathComp,
+    all_edges: &'a [Edge],
+    cycle_indices: &[Pidx],
+) -> Vec<(&'a PathComp, &'a Edge, &'a Edge)> {
+    instance
+        .all_comp_pairs()
+        .filter(|(c, other)| {
+            c.path_idx == comp.path_idx && !cycle_indices.contains(&other.path_idx)
+        })
+        .flat_map(|(_, other)| {
+            let edges_between = all_edges
+                .iter()
+                .filter(|e| e.between_path_nodes(comp.path_idx, other.path_idx))
+                .collect_vec();
+            iproduct!(edges_between.clone(), edges_between).map(move |(e1, e2)| (other, e1, e2))
+        })
+        .collect_vec()
+}
+
 impl PseudoCycle {
     pub fn value(
         &self,
@@ -65,9 +104,31 @@ impl PseudoCycle {
         self.total_component_value(path_comps, all_edges, npc, instance) - self.total_edge_cost
     }
 
+    /// Returns the value of every path component of this cycle, without the shortcuts with other components.
+    /// Useful to find out which component contributes too little to a failed merge.
+    pub fn all_cycle_values(
+        &self,
+        path_comps: &[&PathComp],
+        all_edges: &[Edge],
+        npc: &NicePairConfig,
+        instance: &Instance,
+    ) -> Vec<(Pidx, Credit)> {
+        self.cycle
+            .iter()
+            .flat_map(|(in_node, comp, out_node)| match comp {
+                CycleComp::PathComp(idx) => {
+                    let comp = path_comps[idx.raw()];
+                    let value = self.comp_value(comp, in_node, out_node, npc, all_edges, instance);
+                    Some((*idx, value.base))
+                }
+                CycleComp::Rem => None,
+            })
+            .collect_vec()
+    }
+
     fn total_component_value(
         &self,
-        path_comps: &Vec<&PathComp>,
+        path_comps: &[&PathComp],
         all_edges: &[Edge],
         npc: &NicePairConfig,
         instance: &Instance,
@@ -93,6 +154,9 @@ impl PseudoCycle {
                         self.comp_value(comp, in_node, out_node, npc, all_edges, instance)
                     }
                     CycleComp::Rem => {
+                        // We do not know where the cycle enters and leaves the remaining path, so we can only assume the
+                        // value of a non-shortcutable C4. A nice pair at the source of a rem edge does not help here,
+                        // since it is already accounted for in the value of the source component.
                         CompValue::base(instance.context.inv.two_ec_credit(4)) // non shortcutable C4
                     }
                 }
@@ -121,7 +185,6 @@ impl PseudoCycle {
     ) -> CompValue {
         let nice_pair = npc.is_nice_pair(*in_node, *out_node);
 
-        let path_comps = instance.path_nodes().collect_vec();
         let credit_inv = &instance.context.inv;
 
         let cycle_indices = self
@@ -134,31 +197,18 @@ impl PseudoCycle {
             .cloned()
             .collect_vec();
 
-        let incident_edges = all_edges
-            .iter()
-            .filter(|e| e.path_incident(comp.path_idx))
-            .collect_vec();
-
         match comp.comp.comp_type() {
             CompType::Cycle(4) => {
                 if nice_pair {
                     if comp.comp.is_adjacent(in_node, out_node) {
-                        let local_merge_credits = iproduct!(incident_edges.clone(), incident_edges)
-                            .filter(|(e1, e2)| {
-                                // pair of edges that hits the same comp but not this cycle
-                                e1.other_idx(comp.path_idx) == e2.other_idx(comp.path_idx)
-                                    && !cycle_indices
-                                        .contains(&e2.other_idx(comp.path_idx).unwrap())
-                            })
-                            .map(|(e1, e2)| {
+                        let edge_pairs =
+                            edge_pairs_to_other_comps(instance, comp, all_edges, &cycle_indices);
+                        let local_merge_credits = edge_pairs
+                            .into_iter()
+                            .map(|(hit_comp, e1, e2)| {
                                 let n1 = e1.endpoint_at(comp.path_idx).unwrap();
                                 let n2 = e2.endpoint_at(comp.path_idx).unwrap();
 
-                                let hit_comp = path_comps
-                                    .iter()
-                                    .find(|c| c.path_idx == e2.other_idx(comp.path_idx).unwrap())
-                                    .unwrap();
-
                                 // Credit for shortcutting the other side
                                 let other_shortcut = if npc.is_nice_pair(
                                     e1.endpoint_at(hit_comp.path_idx).unwrap(),
@@ -169,8 +219,8 @@ impl PseudoCycle {
                                     Credit::from_integer(0)
                                 };
 
-                                let credit = if !(n1 == *in_node && n2 == *out_node)
-                                    && !(n2 == *in_node && n1 == *out_node)
+                                let credit = if !((n1 == *in_node && n2 == *out_node)
+                                    || (n2 == *in_node && n1 == *out_node))
                                     && comp.comp.is_adjacent(&n1, &n2)
                                 {
                                     // in this case we can double shortcut C4
@@ -248,21 +298,14 @@ impl PseudoCycle {
                     // in_node == out_node
 
                     // either in == out or !adj(in, out)
-                    let local_merge_credits = iproduct!(incident_edges.clone(), incident_edges)
-                        .filter(|(e1, e2)| {
-                            // pair of edges that hits the same comp but not this cycle
-                            e1.other_idx(comp.path_idx) == e2.other_idx(comp.path_idx)
-                                && !cycle_indices.contains(&e2.other_idx(comp.path_idx).unwrap())
-                        })
-                        .map(|(e1, e2)| {
+                    let edge_pairs =
+                        edge_pairs_to_other_comps(instance, comp, all_edges, &cycle_indices);
+                    let local_merge_credits = edge_pairs
+                        .into_iter()
+                        .map(|(hit_comp, e1, e2)| {
                             let n1 = e1.endpoint_at(comp.path_idx).unwrap();
                             let n2 = e2.endpoint_at(comp.path_idx).unwrap();
 
-                            let hit_comp = path_comps
-                                .iter()
-                                .find(|c| c.path_idx == e2.other_idx(comp.path_idx).unwrap())
-                                .unwrap();
-
                             let other_shortcut = if npc.is_nice_pair(
                                 e1.endpoint_at(hit_comp.path_idx).unwrap(),
                                 e2.endpoint_at(hit_comp.path_idx).unwrap(),
@@ -309,13 +352,11 @@ impl PseudoCycle {
                     //return credit_inv.credits(&comp.comp);
                     let (upper, lower) = comp.comp.paths_between(in_node, out_node);
 
-                    let local_merge_credits = iproduct!(incident_edges.clone(), incident_edges)
-                        .filter(|(e1, e2)| {
-                            // edges must hit same vertec
-                            e1.other_idx(comp.path_idx) == e2.other_idx(comp.path_idx)
-                                && !cycle_indices.contains(&e2.other_idx(comp.path_idx).unwrap())
-                        })
-                        .filter(|(e1, e2)| {
+                    let edge_pairs =
+                        edge_pairs_to_other_comps(instance, comp, all_edges, &cycle_indices);
+                    let local_merge_credits = edge_pairs
+                        .into_iter()
+                        .filter(|(_, e1, e2)| {
                             let n1 = e1.endpoint_at(comp.path_idx).unwrap();
                             let n2 = e2.endpoint_at(comp.path_idx).unwrap();
 
@@ -323,12 +364,7 @@ impl PseudoCycle {
                             (upper.contains(&n1) && lower.contains(&n2))
                                 || (upper.contains(&n2) && lower.contains(&n1))
                         })
-                        .map(|(e1, e2)| {
-                            let hit_comp = path_comps
-                                .iter()
-                                .find(|c| c.path_idx == e2.other_idx(comp.path_idx).unwrap())
-                                .unwrap();
-
+                        .map(|(hit_comp, e1, e2)| {
                             let n1 = e1.endpoint_at(comp.path_idx).unwrap();
                             let n2 = e2.endpoint_at(comp.path_idx).unwrap();
 
@@ -387,3 +423,29 @@ impl PseudoCycle {
         }
     }
 }
+
+#[cfg(test)]
+mod test_cycle_merge {
+    use super::*;
+    use crate::{
+        path::instance::{InstanceContext, StackElement},
+        CreditInv,
+    };
+
+    #[test]
+    fn test_only_rem() {
+        let pc = PseudoCycle::new(
+            vec![(Node::Rem, CycleComp::Rem, Node::Rem); 2],
+            Credit::from_integer(2),
+            &[],
+        );
+        let instance = Instance {
+            stack: vec![StackElement::PseudoCycle(pc)],
+            context: InstanceContext::builder()
+                .inv(CreditInv::new(Credit::new(2, 7)))
+                .build(),
+        };
+        let mut proof = check_cycle_merge(&instance);
+        assert!(!proof.eval().success());
+    }
+}
@@ -69,10 +69,23 @@ pub fn check_path_rearrangement(instance: &Instance, finite: bool) -> PathProofN
     }
 }
 
-// Pidx here means the original pidx
+/// Checks whether the path described by `extension` satisfies the nice path definition.
+///
+/// `extension` describes a new path `start -- inner[0] -- .. -- inner[k] -- end` through components
+/// of the current path. All `Pidx` in `extension` refer to the indices of the current (original) path,
+/// and `end` becomes the last component of the new path, entered via `end_in`. `path_comps` must contain
+/// all components of the current path such that `path_comps[idx.raw()]` is the component with index `idx`.
+/// `npc` is the nice pair configuration used to check the in- and out-nodes.
+///
+/// Every inner component must have a valid in-out pair. If `prelast_is_prelast` is true, the last inner
+/// component (or `start` if there are no inner components) is treated as the prelast component of the new path.
+/// If `finite` is false, the path continues beyond `start`, so also the pair of the original in-node of
+/// `start` and `start_out` must be valid. If `finite` is true, `start` is the end of the path and has no in-node.
+///
+/// Returns a successful leaf if and only if the new path is a nice path with respect to its in- and out-nodes.
 pub fn check_fixed_extension_feasible(
     extension: &Extension,
-    path_comps: &Vec<PathComp>,
+    path_comps: &[PathComp],
     npc: &NicePairConfig,
     prelast_is_prelast: bool,
     finite: bool,
@@ -111,7 +124,7 @@ pub fn check_fixed_extension_feasible(
         let valid_in_out = valid_in_out_npc(
             &start_comp.comp,
             npc,
-            start_comp.in_node.unwrap(),
+            start_comp.in_node_or_panic("rearrangement feasibility"),
             start_out,
             extension.inner.is_empty() && prelast_is_prelast,
             start_comp.used,
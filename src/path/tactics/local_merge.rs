@@ -1,13 +1,15 @@
 use itertools::Itertools;
 
 use crate::{
+    comps::Component,
     path::PathProofNode,
     path::{
         instance::{Instance, InstanceContext},
         NicePairConfig, PathComp,
     },
     types::Edge,
-    Credit,
+    util::get_local_merge_graph,
+    Credit, EdgeType, Graph, Node,
 };
 
 /// Check whether any two or three components can be merged together to a single component. This gives us progress, because we reduce the total number of components.
@@ -37,8 +39,8 @@ pub fn check_local_merge(instance: &Instance) -> PathProofNode {
             None
         });
 
-    if res.is_some() {
-        res.unwrap()
+    if let Some(res) = res {
+        res
     } else {
         // if we reach here, no merge between two components was possible. Now we essentially do the same thing as before, but this time for every three components instead of only two.
         let res = all_comps.iter().permutations(3).find_map(|perm| {
@@ -72,8 +74,8 @@ pub fn check_local_merge(instance: &Instance) -> PathProofNode {
             None
         });
 
-        if res.is_some() {
-            res.unwrap()
+        if let Some(res) = res {
+            res
         } else {
             PathProofNode::new_leaf(
                 "No local merge found between any two zoomed nodes".into(),
@@ -93,9 +95,6 @@ fn merge(
     let left_comp = &left.comp;
     let right_comp = &right.comp;
 
-    // the total credit of the components (e.g. a C4 has 4*c, a Large has 2). This is defined by this credits method.
-    let total_comp_credit = context.inv.credits(left_comp) + context.inv.credits(right_comp);
-
     // iterate through all possible subsets of edges between left and right. Those we want to buy
     for buy in edges_between.iter().powerset().filter(|p| p.len() == 2) {
         // ignore the comment below
@@ -110,15 +109,10 @@ fn merge(
         let r1 = right_comp.incident(buy[0]).unwrap();
         let r2 = right_comp.incident(buy[1]).unwrap();
 
-        let mut credits = total_comp_credit - buy_cost;
-
-        // check if we can shortcut left or right. If yes, we gain one credit, because we can sell an edge.
-        if npc.is_nice_pair(l1, l2) {
-            credits += Credit::from_integer(1)
-        }
-        if npc.is_nice_pair(r1, r2) {
-            credits += Credit::from_integer(1)
-        }
+        // the credits of both components (e.g. a C4 has 4*c, a Large has 2) including the credit for shortcutting them, minus the cost of the bought edges.
+        let credits = merge_credits(left_comp, l1, l2, npc, context)
+            + merge_credits(right_comp, r1, r2, npc, context)
+            - buy_cost;
 
         // Right now, every merge should result into a large component, which needs a credit of 2. so req_credits should always be 2.
         let req_credits = context
@@ -127,7 +121,12 @@ fn merge(
 
         // we finally need to check whether we have enough credits. If yes, we succeeded.
         if credits >= req_credits {
-            return PathProofNode::new_leaf_success("Local merge".into(), credits == req_credits);
+            let graph = get_local_merge_graph(left_comp, right_comp, &vec![(l1, r1), (l2, r2)]);
+            let names = [left.path_idx.to_label(), right.path_idx.to_label()];
+            return PathProofNode::new_leaf_success(
+                format!("Local merge {}", format_graph(&graph, &names)),
+                credits == req_credits,
+            );
         }
         //}
     }
@@ -135,6 +134,41 @@ fn merge(
     PathProofNode::new_leaf("Local merge impossible".into(), false)
 }
 
+/// Lists the merged components and all edges of `graph` with their `EdgeType`, so that a successful merge
+/// can be reconstructed from the proof file.
+fn format_graph(graph: &Graph, component_names: &[String]) -> String {
+    format!(
+        "of [{}]: {}",
+        component_names.join(", "),
+        graph
+            .all_edges()
+            .map(|(u, v, t)| format!("{}-{} ({:?})", u, v, t))
+            .join(", ")
+    )
+}
+
+/// Returns the credits of `comp` (as defined by the credits method) plus one if we can shortcut `comp` between `u` and `v`, which are the endpoints of the two bought edges. Shortcutting lets us sell an edge.
+fn merge_credits(
+    comp: &Component,
+    u: Node,
+    v: Node,
+    npc: &NicePairConfig,
+    context: &InstanceContext,
+) -> Credit {
+    let can_shortcut = if comp.is_c3() {
+        // In a C3 any two distinct vertices are adjacent and connected via the third vertex, so we can shortcut it whenever the bought edges hit different vertices, regardless of the nice pair config.
+        u != v
+    } else {
+        npc.is_nice_pair(u, v)
+    };
+
+    if can_shortcut {
+        context.inv.credits(comp) + Credit::from_integer(1)
+    } else {
+        context.inv.credits(comp)
+    }
+}
+
 // this method does the same as merge but for three components: left - middle - right.
 // The only thing which changes is that we have to enumerate edges between left and middle and middle and right at the same time, to get all combinations.
 fn merge2(
@@ -151,10 +185,6 @@ fn merge2(
 
     let right_comp = &right.comp;
 
-    let total_comp_credit = context.inv.credits(left_comp)
-        + context.inv.credits(middle_comp)
-        + context.inv.credits(right_comp);
-
     for buy1 in edges_between1.iter().powerset().filter(|p| p.len() == 2) {
         for buy2 in edges_between2.iter().powerset().filter(|p| p.len() == 2) {
             let buy_cost: Credit = buy1.iter().map(|e| e.cost).sum::<Credit>()
@@ -169,24 +199,32 @@ fn merge2(
             let r1 = right_comp.incident(buy2[0]).unwrap();
             let r2 = right_comp.incident(buy2[1]).unwrap();
 
-            let mut credits = total_comp_credit - buy_cost;
-
-            if npc.is_nice_pair(l1, l2) {
-                credits += Credit::from_integer(1)
-            }
-            if npc.is_nice_pair(ml1, ml2) || npc.is_nice_pair(mr1, mr2) {
-                credits += Credit::from_integer(1)
-            }
-            if npc.is_nice_pair(r1, r2) {
-                credits += Credit::from_integer(1)
-            }
+            // we can sell at most one edge of the middle component
+            let middle_credits = merge_credits(middle_comp, ml1, ml2, npc, context)
+                .max(merge_credits(middle_comp, mr1, mr2, npc, context));
+            let credits = merge_credits(left_comp, l1, l2, npc, context)
+                + middle_credits
+                + merge_credits(right_comp, r1, r2, npc, context)
+                - buy_cost;
 
             let req_credits = context.inv.two_ec_credit(
                 left_comp.num_edges() + middle_comp.num_edges() + right_comp.num_edges(),
             );
             if credits >= req_credits {
+                let mut graph =
+                    get_local_merge_graph(left_comp, middle_comp, &vec![(l1, ml1), (l2, ml2)]);
+                for (u, v, t) in right_comp.graph().all_edges() {
+                    graph.add_edge(u, v, *t);
+                }
+                graph.add_edge(mr1, r1, EdgeType::Buyable);
+                graph.add_edge(mr2, r2, EdgeType::Buyable);
+                let names = [
+                    left.path_idx.to_label(),
+                    middle.path_idx.to_label(),
+                    right.path_idx.to_label(),
+                ];
                 return PathProofNode::new_leaf_success(
-                    "Local merge".into(),
+                    format!("Local merge {}", format_graph(&graph, &names)),
                     credits == req_credits,
                 );
             }
@@ -196,3 +234,122 @@ fn merge2(
     // if we reach here, not merge was possible.
     PathProofNode::new_leaf("Local merge impossible".into(), false)
 }
+
+#[cfg(test)]
+mod test_local_merge {
+    use super::*;
+    use crate::{
+        comps::{c3, c4, c5},
+        path::{instance::PathNode, Pidx},
+        util::relabels_nodes_sequentially,
+        CreditInv,
+    };
+
+    fn path_comp(comp: Component, path_idx: Pidx) -> PathComp {
+        PathComp {
+            in_node: None,
+            out_node: None,
+            used: false,
+            path_idx,
+            initial_nps: comp.edges(),
+            comp,
+        }
+    }
+
+    fn setup(other: Component) -> (PathComp, PathComp, InstanceContext) {
+        let mut comps = vec![c3(), other];
+        relabels_nodes_sequentially(&mut comps, 0);
+        let context = InstanceContext::builder()
+            .inv(CreditInv::new(Credit::new(1, 4)))
+            .add_comp(PathNode::Unused(comps[0].clone()))
+            .add_comp(PathNode::Unused(comps[1].clone()))
+            .build();
+        let right = path_comp(comps.pop().unwrap(), Pidx::Prelast);
+        let left = path_comp(comps.pop().unwrap(), Pidx::Last);
+        (left, right, context)
+    }
+
+    fn npc(left: &PathComp, right: &PathComp) -> NicePairConfig {
+        NicePairConfig {
+            nice_pairs: [left.initial_nps.clone(), right.initial_nps.clone()].concat(),
+        }
+    }
+
+    #[test]
+    fn test_merge_credits_c3() {
+        let (left, right, context) = setup(c4());
+        let npc = NicePairConfig { nice_pairs: vec![] };
+
+        // a C3 has 3c = 3/4 credits and one more if it can be shortcut between distinct vertices
+        assert_eq!(
+            merge_credits(&left.comp, 0.into(), 1.into(), &npc, &context),
+            Credit::new(7, 4)
+        );
+        assert_eq!(
+            merge_credits(&left.comp, 2.into(), 2.into(), &npc, &context),
+            Credit::new(3, 4)
+        );
+        // other components need a nice pair for shortcutting
+        assert_eq!(
+            merge_credits(&right.comp, 3.into(), 4.into(), &npc, &context),
+            Credit::from_integer(1)
+        );
+
+        // merging a C3 and a C4 via two edges of cost 1 leaves 7/4 + 2 - 2 credits, which is exactly the
+        // credit 7c of the merged component
+        let npc = NicePairConfig {
+            nice_pairs: right.initial_nps.clone(),
+        };
+        let credits = merge_credits(&left.comp, 1.into(), 2.into(), &npc, &context)
+            + merge_credits(&right.comp, 3.into(), 4.into(), &npc, &context)
+            - Credit::from_integer(2);
+        assert_eq!(credits, Credit::new(7, 4));
+        assert_eq!(credits, context.inv.two_ec_credit(7));
+    }
+
+    #[test]
+    fn test_merge_c3_c5() {
+        let (left, right, context) = setup(c5());
+        let npc = npc(&left, &right);
+        let edges = vec![
+            Edge::new(0.into(), Pidx::Last, 3.into(), Pidx::Prelast),
+            Edge::new(1.into(), Pidx::Last, 4.into(), Pidx::Prelast),
+        ];
+        let mut res = merge(&left, &right, &edges, &npc, &context);
+        assert!(res.eval().success());
+        res.assert_complete();
+        // the bought edges are listed in the proof
+        let msg = &res.leaf_messages()[0];
+        assert!(msg.contains("0-3 (Buyable)") && msg.contains("1-4 (Buyable)"));
+    }
+
+    #[test]
+    fn test_merge_c3_c5_no_shortcut() {
+        let (left, right, context) = setup(c5());
+        let npc = npc(&left, &right);
+        // 3 and 5 are not adjacent in the C5 and not a nice pair
+        let edges = vec![
+            Edge::new(0.into(), Pidx::Last, 3.into(), Pidx::Prelast),
+            Edge::new(1.into(), Pidx::Last, 5.into(), Pidx::Prelast),
+        ];
+        let mut res = merge(&left, &right, &edges, &npc, &context);
+        assert!(!res.eval().success());
+        res.assert_complete();
+    }
+
+    #[test]
+    fn test_merge_c3_c4() {
+        let (left, right, context) = setup(c4());
+        // the C3 can be shortcut even if the nice pair config does not contain its edges
+        let npc = NicePairConfig {
+            nice_pairs: right.initial_nps.clone(),
+        };
+        let edges = vec![
+            Edge::new(1.into(), Pidx::Last, 3.into(), Pidx::Prelast),
+            Edge::new(2.into(), Pidx::Last, 4.into(), Pidx::Prelast),
+        ];
+        let mut res = merge(&left, &right, &edges, &npc, &context);
+        assert!(res.eval().tight());
+        res.assert_complete();
+    }
+}
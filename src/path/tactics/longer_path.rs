@@ -21,6 +21,15 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
     let all_comps = instance.path_nodes().cloned().collect_vec();
     let npc = instance.npc();
 
+    for (u, v) in instance.required_nice_pairs() {
+        assert!(
+            npc.is_nice_pair(u, v),
+            "required nice pair ({}, {}) is missing",
+            u,
+            v
+        );
+    }
+
     let mut msg = String::new();
 
     // ignore this for now. This is used if the program previously enumerated a rearrangement of the current nice path, then we would check if we can find a longer nice path based on this rearrangement
@@ -62,7 +71,6 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
         }
     }
 
-
     // We now check if the last comp has feasible outside edges with which we can extend the current nice path
     let last_comp = &all_comps[Pidx::Last.raw()];
     let last_comp_nodes = last_comp.comp.nodes();
@@ -72,7 +80,7 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
         if valid_in_out_npc(
             &last_comp.comp,
             &npc,
-            last_comp.in_node.unwrap(),
+            last_comp.in_node_or_panic("longer path"),
             *outside_hit,
             true,
             last_comp.used,
@@ -121,7 +129,7 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
                 ) {
                     // if yes, we essentially check the rest via the method check_fixed_extension_feasible, which is also used at other places. It simply check for each component whether the nice path definition is satisfied.
                     // The next lines just convert nice_path into a different object, which we can feed into this method.
-                    
+
                     let end = Pidx::Last;
                     let end_in = nice_path.first().unwrap().0;
                     let start = Pidx::from(nice_path.len());
@@ -139,7 +147,6 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
                     // IMPORTANT
                     inner.reverse();
 
-
                     // extension [start.out -- .. -- 2.in:2.out -- 1.in:1.out -- end.in]
                     let extension = Extension {
                         start,
@@ -171,20 +178,18 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
     // Ignore this for now.
     // TODO maybe unnecessary
     if finite {
-        // check if last comp has feasible outside edges
-        let mut rev_comps = all_comps.clone();
-        rev_comps.reverse();
-        let last_comp = &rev_comps[Pidx::Last.raw()];
-        let last_comp_nodes = last_comp.comp.nodes();
+        // check if the start of the path has feasible outside edges, i.e., traverse the path in the opposite direction
+        let first_comp = all_comps.last().unwrap();
+        let first_comp_nodes = first_comp.comp.nodes();
 
-        for outside_hit in all_outside.iter().filter(|n| last_comp_nodes.contains(n)) {
+        for outside_hit in all_outside.iter().filter(|n| first_comp_nodes.contains(n)) {
             if valid_in_out_npc(
-                &last_comp.comp,
+                &first_comp.comp,
                 &npc,
-                last_comp.out_node.unwrap(),
+                first_comp.out_node_or_panic("longer path"),
                 *outside_hit,
                 true,
-                last_comp.used,
+                first_comp.used,
             ) {
                 return PathProofNode::new_leaf(
                     format!("Longer nice path found via outside edge ({})!", outside_hit),
@@ -192,7 +197,7 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
                 );
             }
 
-            let cons_edges = rev_comps
+            let cons_edges = all_comps
                 .windows(2)
                 .map(|w| {
                     all_inter_comp_edges
@@ -211,12 +216,12 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
 
             if cons_edges.is_empty() {
                 if valid_in_out_npc(
-                    &last_comp.comp,
+                    &first_comp.comp,
                     &npc,
-                    last_comp.out_node.unwrap(),
+                    first_comp.out_node_or_panic("longer path"),
                     *outside_hit,
                     true,
-                    last_comp.used,
+                    first_comp.used,
                 ) {
                     return PathProofNode::new_leaf(
                         format!("Longer nice path found via outside edge ({})!", outside_hit),
@@ -228,12 +233,12 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
                 for nice_path in nice_paths {
                     // (0.in -- 1.out):(1.in -- 2.out):(2.in -- 3.out) ... (... -- start.out)
                     if valid_in_out_npc(
-                        &last_comp.comp,
+                        &first_comp.comp,
                         &npc,
-                        nice_path.first().unwrap().0,
+                        nice_path.last().unwrap().1,
                         *outside_hit,
                         true,
-                        last_comp.used,
+                        first_comp.used,
                     ) {
                         let end = Pidx::Last;
                         let end_in = nice_path.first().unwrap().0;
@@ -261,8 +266,13 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
                             inner,
                         };
 
+                        // the new path ends at the start of the current path
                         let mut feasible = check_fixed_extension_feasible(
-                            &extension, &rev_comps, &npc, false, finite,
+                            &extension.reverse(),
+                            &all_comps,
+                            &npc,
+                            false,
+                            finite,
                         );
                         feasible.eval();
                         if feasible.success() {
@@ -280,7 +290,6 @@ pub fn check_longer_nice_path(instance: &Instance, finite: bool) -> PathProofNod
         }
     }
 
-
     // If we reach here, we could not prove that a longer nice path is possible, and thus return false
 
     PathProofNode::new_leaf(
@@ -1,13 +1,16 @@
+use std::str::FromStr;
+
 use itertools::Itertools;
 
-use super::{instance::Instance, PathProofNode};
-use crate::logic::TacticTrait;
+use super::{instance::Instance, PathComp, PathProofNode};
+use crate::{logic::TacticTrait, Node};
 
 mod cycle_merge;
 mod cycle_rearrange;
 mod local_merge;
 mod longer_path;
 mod pendant_rewire;
+mod verify;
 
 #[derive(Debug, Clone)]
 pub enum Tactic {
@@ -20,22 +23,80 @@ pub enum Tactic {
     TacticsExhausted(bool),
 }
 
+impl Tactic {
+    /// The order in which the tactics are tried by default. Cheap tactics come first.
+    pub fn default_ordering() -> Vec<Tactic> {
+        vec![
+            Tactic::FastLongerPath(false),
+            Tactic::LocalMerge,
+            Tactic::Pendant,
+            Tactic::LongerPath(false),
+            Tactic::CycleMerge,
+        ]
+    }
+
+    /// Returns this tactic for the finite or infinite case.
+    pub fn with_finite(&self, finite: bool) -> Tactic {
+        match self {
+            Tactic::LongerPath(_) => Tactic::LongerPath(finite),
+            Tactic::FastLongerPath(_) => Tactic::FastLongerPath(finite),
+            Tactic::Rearrangable(_) => Tactic::Rearrangable(finite),
+            Tactic::TacticsExhausted(_) => Tactic::TacticsExhausted(finite),
+            Tactic::CycleMerge | Tactic::LocalMerge | Tactic::Pendant => self.clone(),
+        }
+    }
+}
+
+/// Returns true if `last` is a C6 or C7 with an edge in `outside`. Then we can just extend the nice path, as we have
+/// no requirements on the in and out of C6 and C7s.
+pub fn is_fast_longer_path(last: &PathComp, outside: &[Node]) -> bool {
+    (last.comp.is_c6() || last.comp.is_c7()) && outside.iter().any(|n| last.comp.contains(n))
+}
+
+impl FromStr for Tactic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "fast_longer_path" => Ok(Tactic::FastLongerPath(false)),
+            "local_merge" => Ok(Tactic::LocalMerge),
+            "pendant" => Ok(Tactic::Pendant),
+            "longer_path" => Ok(Tactic::LongerPath(false)),
+            "cycle_merge" => Ok(Tactic::CycleMerge),
+            _ => Err(format!(
+                "unknown tactic '{}', expected one of fast_longer_path, local_merge, pendant, longer_path, cycle_merge",
+                s
+            )),
+        }
+    }
+}
+
 impl TacticTrait for Tactic {
     type Inst = Instance;
 
     fn prove(&self, stack: &mut Instance) -> PathProofNode {
-        let proof = match self {
+        if let Tactic::LongerPath(finite)
+        | Tactic::FastLongerPath(finite)
+        | Tactic::Rearrangable(finite)
+        | Tactic::TacticsExhausted(finite) = self
+        {
+            debug_assert_eq!(
+                *finite,
+                stack.is_finite(),
+                "{:?} is applied to an instance of the other case",
+                self
+            );
+        }
+
+        let mut proof = match self {
             Tactic::FastLongerPath(_finite) => {
                 let outside = stack.out_edges();
-                let path_comps = stack.path_nodes().collect_vec();
-                let last = path_comps.first().unwrap();
-                if (last.comp.is_c6() || last.comp.is_c7())
-                    && outside.iter().any(|n| last.comp.contains(n))
-                {
-                    // if the last component is a c6 or c7, we can just extend the nice path, as we have no requirements on the in and out of c6 and c7s.
-                    return PathProofNode::new_leaf("fast_longer_path".into(), true);
+                let last = stack.first_comp().unwrap();
+                if is_fast_longer_path(last, &outside) {
+                    PathProofNode::new_leaf("fast_longer_path".into(), true)
+                } else {
+                    PathProofNode::new_leaf("no fast_longer_path".into(), false)
                 }
-                PathProofNode::new_leaf("no fast_longer_path".into(), false)
             }
             Tactic::LongerPath(finite) => longer_path::check_longer_nice_path(stack, *finite),
             Tactic::CycleMerge => cycle_merge::check_cycle_merge(stack),
@@ -67,6 +128,9 @@ impl TacticTrait for Tactic {
 
                 if *finite {
                     log::info!("tactics (finite) exhausted for: {}", msg);
+                    for hint in exhausted_hints(stack) {
+                        log::info!("hint: {}", hint);
+                    }
                     PathProofNode::new_leaf("Tactics (finite) exhausted!".into(), false)
                 } else {
                     log::info!("tactics exhausted for: {}", msg);
@@ -91,6 +155,38 @@ impl TacticTrait for Tactic {
               //     PathProofNode::new_leaf(msg, false)
               // }
         };
+
+        if stack.context.verify_mode && proof.eval().success() {
+            verify::verify_success(self, stack);
+        }
         proof
     }
 }
+
+/// Heuristic suggestions why no tactic succeeded on `stack`, to make failed proofs easier to analyze.
+fn exhausted_hints(stack: &Instance) -> Vec<&'static str> {
+    let mut hints = vec![];
+    let path_comps = stack.path_nodes().collect_vec();
+
+    if stack.rem_edges().is_empty() && path_comps.iter().all(|c| c.comp.is_c4() || c.comp.is_c5()) {
+        hints.push(
+            "only C4s and C5s without edges to the remaining path: consider increasing max_depth",
+        );
+    }
+
+    if let Some(last) = stack.first_comp() {
+        if stack.out_edges().iter().any(|n| last.comp.contains(n)) {
+            hints.push(
+                "last component has an outside edge: check NicePairConfig for last component",
+            );
+        }
+    }
+
+    if path_comps.iter().any(|c| {
+        (c.comp.is_c6() || c.comp.is_c7()) && !stack.is_contractability_checked(c.path_idx)
+    }) {
+        hints.push("some C6 or C7 was not checked for contractability");
+    }
+
+    hints
+}
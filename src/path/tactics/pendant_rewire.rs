@@ -7,7 +7,6 @@ pub fn check_pendant_node(instance: &Instance) -> PathProofNode {
     let all_edges = instance.all_inter_comp_edges();
     let outside = instance.out_edges();
     let mut path_comps = instance.path_nodes();
-    let rem_edges = instance.rem_edges();
 
     let last_comp_nodes = &path_comps.next().unwrap().comp.nodes();
 
@@ -25,7 +24,7 @@ pub fn check_pendant_node(instance: &Instance) -> PathProofNode {
 
     let c = outside.iter().all(|n| !last_comp_nodes.contains(n));
 
-    let d = rem_edges.iter().all(|e| e.source_idx != Pidx::Last);
+    let d = instance.rem_edges_from(Pidx::Last).is_empty();
 
     if a && b && c && d {
         PathProofNode::new_leaf("Rewire pendant node!".to_string(), true)
@@ -0,0 +1,191 @@
+use itertools::Itertools;
+
+use crate::path::{
+    instance::Instance, path_definition::valid_in_out_npc, pseudo_cycle::CycleComp, PathComp, Pidx,
+};
+
+use super::Tactic;
+
+/// Checks that `instance` satisfies necessary conditions for `tactic` to succeed, which are recomputed independently
+/// of the tactic. This does not redo the proof of the tactic; only for `Pendant` the conditions are also sufficient.
+/// Panics if the conditions do not hold.
+pub fn verify_success(tactic: &Tactic, instance: &Instance) {
+    let consistent = match tactic {
+        Tactic::FastLongerPath(_) => {
+            let last = instance.first_comp().unwrap();
+            (last.comp.is_c6() || last.comp.is_c7()) && has_outside_edge(instance, last)
+        }
+        Tactic::LongerPath(finite) => {
+            let path_comps = instance.path_nodes().collect_vec();
+            let candidates = if let Some(rearrangement) = instance.rearrangement() {
+                vec![path_comps[rearrangement.end.raw()]]
+            } else if *finite {
+                vec![path_comps[Pidx::Last.raw()], *path_comps.last().unwrap()]
+            } else {
+                vec![path_comps[Pidx::Last.raw()]]
+            };
+            candidates
+                .into_iter()
+                .any(|comp| has_valid_extension(instance, comp))
+        }
+        Tactic::CycleMerge => instance.pseudo_cycle().is_some_and(|pc| {
+            pc.cycle
+                .iter()
+                .any(|(_, c, _)| matches!(c, CycleComp::PathComp(_)))
+        }),
+        Tactic::LocalMerge => {
+            let all_edges = instance.all_inter_comp_edges();
+            instance
+                .path_nodes()
+                .collect_vec()
+                .into_iter()
+                .tuple_combinations::<(_, _)>()
+                .any(|(left, right)| {
+                    all_edges
+                        .iter()
+                        .filter(|e| e.between_path_nodes(left.path_idx, right.path_idx))
+                        .count()
+                        >= 2
+                })
+        }
+        Tactic::Rearrangable(_) => instance.rearrangement().is_some(),
+        Tactic::Pendant => {
+            let last = instance.first_comp().unwrap();
+            // path indices of the endpoints are recomputed from the nodes instead of taken from the edges
+            let incident = instance
+                .all_inter_comp_edges()
+                .into_iter()
+                .map(|e| {
+                    (
+                        instance.index_of_super_node(e.n1),
+                        instance.index_of_super_node(e.n2),
+                    )
+                })
+                .filter(|(i1, i2)| *i1 == last.path_idx || *i2 == last.path_idx)
+                .collect_vec();
+            // exactly three edges, all to the prelast component, and no outside or REM edges
+            incident.len() == 3
+                && incident
+                    .iter()
+                    .all(|(i1, i2)| *i1 == Pidx::Prelast || *i2 == Pidx::Prelast)
+                && !has_outside_edge(instance, last)
+                && instance
+                    .rem_edges()
+                    .iter()
+                    .all(|e| !last.comp.contains(&e.source))
+        }
+        Tactic::TacticsExhausted(_) => false,
+    };
+
+    assert!(
+        consistent,
+        "Verification failed: {:?} succeeded on an instance which does not satisfy its requirements: {}",
+        tactic, instance
+    );
+}
+
+fn has_outside_edge(instance: &Instance, comp: &PathComp) -> bool {
+    instance.out_edges().iter().any(|n| comp.comp.contains(n))
+}
+
+/// Returns true if `comp` has an outside edge such that, for some in node, the in node and the outside edge form a
+/// valid in-out pair of a prelast component.
+fn has_valid_extension(instance: &Instance, comp: &PathComp) -> bool {
+    let npc = instance.npc();
+    instance
+        .out_edges()
+        .into_iter()
+        .filter(|n| comp.comp.contains(n))
+        .any(|outside_hit| {
+            comp.comp.nodes().iter().any(|in_node| {
+                valid_in_out_npc(&comp.comp, &npc, *in_node, outside_hit, true, comp.used)
+            })
+        })
+}
+
+#[cfg(test)]
+mod test_verify {
+    use super::*;
+    use crate::{
+        comps::{c5, c6},
+        path::instance::{InstPart, InstanceContext, StackElement},
+        path::tactics::pendant_rewire::check_pendant_node,
+        types::Edge,
+        util::relabels_nodes_sequentially,
+        Credit, CreditInv, Node,
+    };
+
+    fn c6_instance(outside: bool) -> Instance {
+        let comp = c6();
+        let mut part = InstPart::new_path_comp(PathComp {
+            in_node: Some(comp.fixed_node()),
+            out_node: None,
+            comp: comp.clone(),
+            used: false,
+            path_idx: Pidx::Last,
+            initial_nps: comp.edges(),
+        });
+        if outside {
+            part.out_edges.push(comp.nodes()[2]);
+        }
+        Instance {
+            stack: vec![StackElement::Inst(part)],
+            context: InstanceContext::builder()
+                .inv(CreditInv::new(Credit::new(2, 7)))
+                .build(),
+        }
+    }
+
+    #[test]
+    fn test_fast_longer_path() {
+        verify_success(&Tactic::FastLongerPath(false), &c6_instance(true));
+        verify_success(&Tactic::LongerPath(false), &c6_instance(true));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fast_longer_path_without_outside_edge() {
+        verify_success(&Tactic::FastLongerPath(false), &c6_instance(false));
+    }
+
+    /// A C5 as last component with `num_edges` edges in addition to the path edge to a C5 as prelast component.
+    fn pendant_instance(num_edges: u32) -> Instance {
+        let mut comps = vec![c5(), c5()];
+        relabels_nodes_sequentially(&mut comps, 0);
+        let mut instance = Instance {
+            stack: vec![],
+            context: InstanceContext::builder()
+                .inv(CreditInv::new(Credit::new(2, 7)))
+                .build(),
+        };
+        for comp in comps {
+            instance.extend_with_comp(PathComp {
+                in_node: Some(comp.fixed_node()),
+                out_node: Some(comp.fixed_node()),
+                initial_nps: comp.edges(),
+                comp,
+                used: false,
+                path_idx: Pidx::Last,
+            });
+        }
+        let mut part = InstPart::empty();
+        part.edges = (1..=num_edges)
+            .map(|i| Edge::new(Node::n(i), Pidx::Last, Node::n(5 + i), Pidx::Prelast))
+            .collect_vec();
+        instance.stack.push(StackElement::Inst(part));
+        instance
+    }
+
+    #[test]
+    fn test_pendant() {
+        let instance = pendant_instance(2);
+        assert!(check_pendant_node(&instance).eval().success());
+        verify_success(&Tactic::Pendant, &instance);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pendant_with_four_edges() {
+        verify_success(&Tactic::Pendant, &pendant_instance(3));
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fmt::{self, Display, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Outcome {
     True,
     False,
@@ -12,10 +15,48 @@ impl Outcome {
         !matches!(self, Outcome::False)
     }
 
-    #[allow(dead_code)]
     pub fn tight(&self) -> bool {
         matches!(self, Outcome::Tight)
     }
+
+    /// Conjunction of two outcomes: `False` if any is `False`, otherwise `Tight` if any is `Tight`.
+    pub fn and(self, other: Outcome) -> Outcome {
+        match (self, other) {
+            (Outcome::False, _) | (_, Outcome::False) => Outcome::False,
+            (Outcome::Tight, _) | (_, Outcome::Tight) => Outcome::Tight,
+            _ => Outcome::True,
+        }
+    }
+
+    /// Disjunction of two outcomes: `Tight` if any is `Tight`, otherwise `True` if any is `True`.
+    pub fn or(self, other: Outcome) -> Outcome {
+        match (self, other) {
+            (Outcome::Tight, _) | (_, Outcome::Tight) => Outcome::Tight,
+            (Outcome::True, _) | (_, Outcome::True) => Outcome::True,
+            _ => Outcome::False,
+        }
+    }
+}
+
+/// Upper bound on the number of children added to `All`/`Any` nodes, shared by all clones.
+#[derive(Clone, Debug)]
+pub struct NodeLimit {
+    max_nodes: usize,
+    count: Arc<AtomicUsize>,
+}
+
+impl NodeLimit {
+    pub fn new(max_nodes: usize) -> Self {
+        NodeLimit {
+            max_nodes,
+            count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Counts one more node. Returns false if this exceeds the limit.
+    pub fn try_add(&self) -> bool {
+        self.count.fetch_add(1, Ordering::SeqCst) < self.max_nodes
+    }
 }
 
 #[derive(Clone)]
@@ -139,6 +180,96 @@ impl ProofNode {
         }
     }
 
+    /// Returns the children which evaluated to `False`. Must be called after `eval`.
+    pub fn get_failing_children(&self) -> Vec<&ProofNode> {
+        match self {
+            ProofNode::Leaf(_) => vec![],
+            ProofNode::Info(node) => [node.child.as_ref()]
+                .into_iter()
+                .filter(|c| !c.success())
+                .collect(),
+            ProofNode::Or(node) => [node.child1.as_ref(), node.child2.as_ref()]
+                .into_iter()
+                .filter(|c| !c.success())
+                .collect(),
+            ProofNode::All(node) | ProofNode::Any(node) => {
+                node.childs.iter().filter(|c| !c.success()).collect()
+            }
+        }
+    }
+
+    /// Returns the messages of all leaves in depth-first order. Note that `eval_and_prune` removes
+    /// the children of successful nodes.
+    pub fn leaf_messages(&self) -> Vec<String> {
+        self.leaves().into_iter().map(|l| l.msg.clone()).collect()
+    }
+
+    /// Returns the messages of all leaves with outcome `True` in depth-first order.
+    pub fn successful_leaf_messages(&self) -> Vec<String> {
+        self.leaves()
+            .into_iter()
+            .filter(|l| l.outcome == Outcome::True)
+            .map(|l| l.msg.clone())
+            .collect()
+    }
+
+    /// Returns the outcomes of all leaves in depth-first order.
+    pub fn all_leaf_outcomes(&self) -> Vec<Outcome> {
+        self.leaves().into_iter().map(|l| l.outcome).collect()
+    }
+
+    /// Counts how many leaves have each outcome. Like `leaf_messages`, this only sees the leaves which
+    /// are left after pruning.
+    pub fn outcome_histogram(&self) -> HashMap<Outcome, usize> {
+        let mut histogram = HashMap::new();
+        for outcome in self.all_leaf_outcomes() {
+            *histogram.entry(outcome).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    fn leaves(&self) -> Vec<&LeafNode> {
+        match self {
+            ProofNode::Leaf(node) => vec![node],
+            ProofNode::Info(node) => node.child.leaves(),
+            ProofNode::Or(node) => [node.child1.leaves(), node.child2.leaves()].concat(),
+            ProofNode::All(node) | ProofNode::Any(node) => {
+                node.childs.iter().flat_map(|c| c.leaves()).collect()
+            }
+        }
+    }
+
+    /// Returns the maximum depth of a successful (`True` or `Tight`) leaf, where depth is counted as in
+    /// `print_tree`. Successful leaves deeper than `output_depth` are never printed.
+    pub fn max_true_depth(&self) -> Option<usize> {
+        self.max_leaf_depth(0, &|outcome| outcome.success())
+    }
+
+    /// Returns the maximum depth of a `False` leaf, where depth is counted as in `print_tree`.
+    pub fn max_false_depth(&self) -> Option<usize> {
+        self.max_leaf_depth(0, &|outcome| !outcome.success())
+    }
+
+    fn max_leaf_depth(&self, depth: usize, pred: &dyn Fn(Outcome) -> bool) -> Option<usize> {
+        let new_depth = match self {
+            ProofNode::Leaf(_) | ProofNode::Info(_) if !self.is_msg_empty() => depth + 1,
+            _ => depth,
+        };
+        match self {
+            ProofNode::Leaf(node) => pred(node.outcome).then_some(depth),
+            ProofNode::Info(node) => node.child.max_leaf_depth(new_depth, pred),
+            ProofNode::Or(node) => [&node.child1, &node.child2]
+                .into_iter()
+                .filter_map(|c| c.max_leaf_depth(new_depth, pred))
+                .max(),
+            ProofNode::All(node) | ProofNode::Any(node) => node
+                .childs
+                .iter()
+                .filter_map(|c| c.max_leaf_depth(new_depth, pred))
+                .max(),
+        }
+    }
+
     pub fn add_child(&mut self, child: ProofNode) {
         match self {
             ProofNode::All(node) | ProofNode::Any(node) => node.childs.push(child),
@@ -160,16 +291,11 @@ impl ProofNode {
                 if let Some(s) = node.outcome {
                     return s;
                 }
-                node.outcome = Some(Outcome::True);
-                for c in &mut node.childs {
-                    match c.eval() {
-                        Outcome::False => node.outcome = Some(Outcome::False),
-                        Outcome::Tight if node.outcome == Some(Outcome::True) => {
-                            node.outcome = Some(Outcome::Tight)
-                        }
-                        _ => {}
-                    }
-                }
+                node.outcome = Some(
+                    node.childs
+                        .iter_mut()
+                        .fold(Outcome::True, |acc, c| acc.and(c.eval())),
+                );
                 let outcome = node.outcome.unwrap();
                 if outcome.success() || node.msg == "pc" {
                     node.childs.clear();
@@ -180,16 +306,11 @@ impl ProofNode {
                 if let Some(s) = node.outcome {
                     return s;
                 }
-                node.outcome = Some(Outcome::False);
-                for c in &mut node.childs {
-                    match c.eval() {
-                        Outcome::Tight => node.outcome = Some(Outcome::Tight),
-                        Outcome::True if node.outcome == Some(Outcome::False) => {
-                            node.outcome = Some(Outcome::True)
-                        }
-                        _ => {}
-                    }
-                }
+                node.outcome = Some(
+                    node.childs
+                        .iter_mut()
+                        .fold(Outcome::False, |acc, c| acc.or(c.eval())),
+                );
                 let outcome = node.outcome.unwrap();
                 if outcome.success() || node.msg == "pc" {
                     node.childs.clear();
@@ -203,17 +324,146 @@ impl ProofNode {
                 let r1 = node.child1.eval();
                 let r2 = node.child2.eval();
 
-                node.outcome = match (r1, r2) {
-                    (Outcome::Tight, _) | (_, Outcome::Tight) => Some(Outcome::Tight),
-                    (Outcome::True, _) | (_, Outcome::True) => Some(Outcome::True),
-                    _ => Some(Outcome::False),
-                };
+                node.outcome = Some(r1.or(r2));
 
                 node.outcome.unwrap()
             }
         }
     }
 
+    /// Panics if some node has no outcome, i.e., was not evaluated. The panic message contains the
+    /// messages of the nodes on the path to the first such node. Call this after `eval`.
+    pub fn assert_complete(&self) {
+        if let Some(path) = self.first_incomplete_path() {
+            panic!("proof node without outcome: {}", path.join(" -> "));
+        }
+    }
+
+    fn first_incomplete_path(&self) -> Option<Vec<String>> {
+        let (outcome, msg, childs) = match self {
+            ProofNode::Leaf(_) => return None,
+            ProofNode::Info(node) => (node.outcome, node.msg.clone(), vec![&*node.child]),
+            ProofNode::All(node) | ProofNode::Any(node) => {
+                (node.outcome, node.msg.clone(), node.childs.iter().collect())
+            }
+            ProofNode::Or(node) => (
+                node.outcome,
+                "or".to_string(),
+                vec![&*node.child1, &*node.child2],
+            ),
+        };
+        if outcome.is_none() {
+            return Some(vec![msg]);
+        }
+        childs
+            .into_iter()
+            .find_map(|child| child.first_incomplete_path())
+            .map(|mut path| {
+                path.insert(0, msg);
+                path
+            })
+    }
+
+    /// Returns the messages of all info nodes on the path to the first failing leaf, in order from the
+    /// root. These describe the case which could not be proved. Returns `None` if no leaf fails.
+    pub fn info_messages_to_failing_leaf(&self) -> Option<Vec<String>> {
+        match self {
+            ProofNode::Leaf(node) => (!node.outcome.success()).then(Vec::new),
+            ProofNode::Info(node) => node.child.info_messages_to_failing_leaf().map(|mut msgs| {
+                msgs.insert(0, node.msg.clone());
+                msgs
+            }),
+            ProofNode::Or(node) => [&node.child1, &node.child2]
+                .into_iter()
+                .find_map(|child| child.info_messages_to_failing_leaf()),
+            ProofNode::All(node) | ProofNode::Any(node) => node
+                .childs
+                .iter()
+                .find_map(|child| child.info_messages_to_failing_leaf()),
+        }
+    }
+
+    /// Returns a copy of this tree in which all `Tight` outcomes, including the cached outcomes of inner
+    /// nodes, are replaced by `True`.
+    pub fn without_tight(&self) -> ProofNode {
+        let mut node = self.clone();
+        node.replace_tight();
+        node
+    }
+
+    fn replace_tight(&mut self) {
+        let untight = |outcome| match outcome {
+            Outcome::Tight => Outcome::True,
+            outcome => outcome,
+        };
+        match self {
+            ProofNode::Leaf(node) => node.outcome = untight(node.outcome),
+            ProofNode::Info(node) => {
+                node.outcome = node.outcome.map(untight);
+                node.child.replace_tight();
+            }
+            ProofNode::Or(node) => {
+                node.outcome = node.outcome.map(untight);
+                node.child1.replace_tight();
+                node.child2.replace_tight();
+            }
+            ProofNode::All(node) | ProofNode::Any(node) => {
+                node.outcome = node.outcome.map(untight);
+                node.childs
+                    .iter_mut()
+                    .for_each(|child| child.replace_tight());
+            }
+        }
+    }
+
+    /// Returns the subtree which only contains the branches leading to a leaf with outcome `target`, or
+    /// `None` if there is no such leaf. An or-node with only one such branch is replaced by this branch.
+    /// The outcomes of the retained nodes are not changed.
+    pub fn retain_path_to_outcome(&self, target: Outcome) -> Option<ProofNode> {
+        match self {
+            ProofNode::Leaf(node) => (node.outcome == target).then(|| self.clone()),
+            ProofNode::Info(node) => node.child.retain_path_to_outcome(target).map(|child| {
+                ProofNode::Info(InfoNode {
+                    msg: node.msg.clone(),
+                    outcome: node.outcome,
+                    child: Box::new(child),
+                })
+            }),
+            ProofNode::Or(node) => match (
+                node.child1.retain_path_to_outcome(target),
+                node.child2.retain_path_to_outcome(target),
+            ) {
+                (Some(child1), Some(child2)) => Some(ProofNode::Or(OrNode {
+                    outcome: node.outcome,
+                    child1: Box::new(child1),
+                    child2: Box::new(child2),
+                })),
+                (Some(child), None) | (None, Some(child)) => Some(child),
+                (None, None) => None,
+            },
+            ProofNode::All(node) | ProofNode::Any(node) => {
+                let childs = node
+                    .childs
+                    .iter()
+                    .filter_map(|child| child.retain_path_to_outcome(target))
+                    .collect::<Vec<_>>();
+                if childs.is_empty() {
+                    return None;
+                }
+                let inner = InnerNode {
+                    msg: node.msg.clone(),
+                    outcome: node.outcome,
+                    childs,
+                };
+                if let ProofNode::All(_) = self {
+                    Some(ProofNode::All(inner))
+                } else {
+                    Some(ProofNode::Any(inner))
+                }
+            }
+        }
+    }
+
     pub fn eval(&mut self) -> Outcome {
         match self {
             ProofNode::Leaf(node) => node.outcome,
@@ -228,32 +478,22 @@ impl ProofNode {
                 if let Some(s) = node.outcome {
                     return s;
                 }
-                node.outcome = Some(Outcome::True);
-                for c in &mut node.childs {
-                    match c.eval() {
-                        Outcome::False => node.outcome = Some(Outcome::False),
-                        Outcome::Tight if node.outcome == Some(Outcome::True) => {
-                            node.outcome = Some(Outcome::Tight)
-                        }
-                        _ => {}
-                    }
-                }
+                node.outcome = Some(
+                    node.childs
+                        .iter_mut()
+                        .fold(Outcome::True, |acc, c| acc.and(c.eval())),
+                );
                 node.outcome.unwrap()
             }
             ProofNode::Any(node) => {
                 if let Some(s) = node.outcome {
                     return s;
                 }
-                node.outcome = Some(Outcome::False);
-                for c in &mut node.childs {
-                    match c.eval() {
-                        Outcome::Tight => node.outcome = Some(Outcome::Tight),
-                        Outcome::True if node.outcome == Some(Outcome::False) => {
-                            node.outcome = Some(Outcome::True)
-                        }
-                        _ => {}
-                    }
-                }
+                node.outcome = Some(
+                    node.childs
+                        .iter_mut()
+                        .fold(Outcome::False, |acc, c| acc.or(c.eval())),
+                );
                 node.outcome.unwrap()
             }
             ProofNode::Or(node) => {
@@ -263,11 +503,7 @@ impl ProofNode {
                 let r1 = node.child1.eval();
                 let r2 = node.child2.eval();
 
-                node.outcome = match (r1, r2) {
-                    (Outcome::Tight, _) | (_, Outcome::Tight) => Some(Outcome::Tight),
-                    (Outcome::True, _) | (_, Outcome::True) => Some(Outcome::True),
-                    _ => Some(Outcome::False),
-                };
+                node.outcome = Some(r1.or(r2));
 
                 node.outcome.unwrap()
             }
@@ -303,17 +539,11 @@ impl ProofNode {
         max_depth_true: usize,
     ) -> anyhow::Result<()> {
         let mut new_depth = depth;
-        match self {
-            ProofNode::Leaf(_) | ProofNode::Info(_) => {
-                //| ProofNode::All(_) | ProofNode::Any(_) => {
-                if !self.is_msg_empty() {
-                    new_depth += 1;
-                    (0..depth).try_for_each(|_| write!(writer, "  "))?;
-                    writeln!(writer, "{}", self.msg())?;
-                }
-            }
-            _ => { // dont print or's
-            }
+        // dont print or's
+        if matches!(self, ProofNode::Leaf(_) | ProofNode::Info(_)) && !self.is_msg_empty() {
+            new_depth += 1;
+            (0..depth).try_for_each(|_| write!(writer, "  "))?;
+            writeln!(writer, "{}", self.msg())?;
         }
 
         match &self {
@@ -345,6 +575,88 @@ impl ProofNode {
 
         Ok(())
     }
+
+    /// Writes the proof tree as a LaTeX `forest` environment, where every node is labeled with its
+    /// message and outcome. Like `print_tree`, or-nodes and nodes without message are skipped, and
+    /// successful subtrees are only printed up to depth `max_depth_true`. Call this after `eval`.
+    #[cfg(feature = "latex-output")]
+    pub fn to_tikz<W: Write>(&self, writer: &mut W, max_depth_true: usize) -> anyhow::Result<()> {
+        writeln!(writer, "\\begin{{forest}}")?;
+        if self.is_msg_empty() {
+            // forest requires a single root
+            writeln!(writer, "[{{}}")?;
+            self.to_tikz_rec(writer, 1, max_depth_true)?;
+            writeln!(writer, "]")?;
+        } else {
+            self.to_tikz_rec(writer, 0, max_depth_true)?;
+        }
+        writeln!(writer, "\\end{{forest}}")?;
+        Ok(())
+    }
+
+    #[cfg(feature = "latex-output")]
+    fn to_tikz_rec<W: Write>(
+        &self,
+        writer: &mut W,
+        depth: usize,
+        max_depth_true: usize,
+    ) -> anyhow::Result<()> {
+        let childs = match self {
+            ProofNode::Leaf(_) => vec![],
+            ProofNode::Info(node) => vec![&*node.child],
+            ProofNode::Or(node) => vec![&*node.child1, &*node.child2],
+            ProofNode::All(node) | ProofNode::Any(node) => node.childs.iter().collect(),
+        };
+
+        let labeled = !self.is_msg_empty();
+        let new_depth = if labeled { depth + 1 } else { depth };
+        if labeled {
+            let (msg, outcome) = match self {
+                ProofNode::Leaf(node) => (&node.msg, node.outcome),
+                ProofNode::Info(node) => (&node.msg, node.outcome.unwrap()),
+                ProofNode::All(node) | ProofNode::Any(node) => (&node.msg, node.outcome.unwrap()),
+                ProofNode::Or(_) => unreachable!("or-nodes have no message"),
+            };
+            let symbol = match outcome {
+                Outcome::True => "$\\checkmark$",
+                Outcome::Tight => "$\\checkmark^{=}$",
+                Outcome::False => "$\\times$",
+            };
+            (0..depth).try_for_each(|_| write!(writer, "  "))?;
+            writeln!(writer, "[{{{} {}}}", escape_latex(msg), symbol)?;
+        }
+
+        for c in childs {
+            if !(c.outcome().success() && depth >= max_depth_true) {
+                c.to_tikz_rec(writer, new_depth, max_depth_true)?;
+            }
+        }
+
+        if labeled {
+            (0..depth).try_for_each(|_| write!(writer, "  "))?;
+            writeln!(writer, "]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes the characters which have a special meaning in LaTeX.
+#[cfg(feature = "latex-output")]
+fn escape_latex(msg: &str) -> String {
+    let mut escaped = String::with_capacity(msg.len());
+    for c in msg.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 impl Display for ProofNode {
@@ -369,3 +681,97 @@ impl Display for ProofNode {
         }
     }
 }
+
+#[cfg(test)]
+mod test_proof_tree {
+    use super::*;
+
+    #[test]
+    fn test_info_messages_to_failing_leaf() {
+        let mut all = ProofNode::new_all("all".into());
+        all.add_child(ProofNode::new_info(
+            "case 1".into(),
+            ProofNode::new_leaf("proved".into(), true),
+        ));
+        all.add_child(ProofNode::new_info(
+            "case 2".into(),
+            ProofNode::new_info(
+                "subcase 2.1".into(),
+                ProofNode::new_leaf("failed".into(), false),
+            ),
+        ));
+        all.eval();
+        assert_eq!(
+            all.info_messages_to_failing_leaf(),
+            Some(vec!["case 2".to_string(), "subcase 2.1".to_string()])
+        );
+
+        let mut proved = ProofNode::new_info("case".into(), ProofNode::new_leaf("".into(), true));
+        proved.eval();
+        assert_eq!(proved.info_messages_to_failing_leaf(), None);
+    }
+
+    #[test]
+    fn test_retain_path_to_outcome() {
+        let mut any = ProofNode::new_any("any".into());
+        any.add_child(ProofNode::new_leaf("failed 1".into(), false));
+        any.add_child(ProofNode::new_or(
+            ProofNode::new_leaf("failed 2".into(), false),
+            ProofNode::new_leaf("proved".into(), true),
+        ));
+        any.eval();
+
+        let failing = any.retain_path_to_outcome(Outcome::False).unwrap();
+        assert_eq!(failing.leaf_messages(), vec!["failed 1", "failed 2"]);
+        assert_eq!(failing.outcome(), Outcome::True);
+
+        let proved = any.retain_path_to_outcome(Outcome::True).unwrap();
+        assert_eq!(proved.leaf_messages(), vec!["proved"]);
+
+        assert!(any.retain_path_to_outcome(Outcome::Tight).is_none());
+    }
+
+    #[test]
+    fn test_without_tight() {
+        let mut all = ProofNode::new_all("all".into());
+        all.add_child(ProofNode::new_leaf_success("tight".into(), true));
+        all.add_child(ProofNode::new_leaf_success("true".into(), false));
+        all.add_child(ProofNode::new_info(
+            "info".into(),
+            ProofNode::new_leaf_success("tight".into(), true),
+        ));
+        assert_eq!(all.eval(), Outcome::Tight);
+
+        let mut untight = all.without_tight();
+        assert_eq!(untight.outcome(), Outcome::True);
+        assert_eq!(untight.all_leaf_outcomes(), vec![Outcome::True; 3]);
+        assert_eq!(untight.eval(), Outcome::True);
+        // the original tree is unchanged
+        assert_eq!(all.all_leaf_outcomes()[0], Outcome::Tight);
+    }
+
+    #[cfg(feature = "latex-output")]
+    #[test]
+    fn test_to_tikz() {
+        let mut all = ProofNode::new_all("all".into());
+        all.add_child(ProofNode::new_or(
+            ProofNode::new_leaf("no_merge".into(), false),
+            ProofNode::new_leaf("merge 50%".into(), true),
+        ));
+        all.eval();
+
+        let mut buf = String::new();
+        all.to_tikz(&mut buf, 10).unwrap();
+        assert_eq!(
+            buf,
+            "\\begin{forest}\n\
+             [{all $\\checkmark$}\n\
+             \x20 [{no\\_merge $\\times$}\n\
+             \x20 ]\n\
+             \x20 [{merge 50\\% $\\checkmark$}\n\
+             \x20 ]\n\
+             ]\n\
+             \\end{forest}\n"
+        );
+    }
+}
@@ -60,11 +60,11 @@ impl Edge {
         )
     }
 
-    pub fn to_tuple(&self) -> (Node, Node) {
+    pub fn to_tuple(self) -> (Node, Node) {
         (self.n1, self.n2)
     }
 
-    pub fn to_vec(&self) -> Vec<Node> {
+    pub fn to_vec(self) -> Vec<Node> {
         vec![self.n1, self.n2]
     }
 
@@ -78,6 +78,7 @@ impl Edge {
         }
     }
 
+    /// Returns the first endpoint of this edge which is contained in `nodes`.
     pub fn endpoint_in(&self, nodes: &[Node]) -> Option<Node> {
         if nodes.contains(&self.n1) {
             Some(self.n1)
@@ -92,6 +93,13 @@ impl Edge {
         nodes.contains(&self.n1) || nodes.contains(&self.n2)
     }
 
+    /// Returns true if both endpoints of this edge are contained in `nodes`.
+    #[cfg(test)]
+    pub fn both_endpoints_in(&self, nodes: &[Node]) -> bool {
+        nodes.contains(&self.n1) && nodes.contains(&self.n2)
+    }
+
+    /// Returns true if exactly one endpoint of this edge is contained in `nodes`.
     pub fn one_sided_nodes_incident(&self, nodes: &[Node]) -> bool {
         (nodes.contains(&self.n1) && !nodes.contains(&self.n2))
             || (nodes.contains(&self.n2) && !nodes.contains(&self.n1))
@@ -110,9 +118,9 @@ impl Edge {
         if n == &self.n1 {
             Some(self.n2)
         } else if n == &self.n2 {
-            return Some(self.n1);
+            Some(self.n1)
         } else {
-            return None;
+            None
         }
     }
 
@@ -121,7 +129,7 @@ impl Edge {
         if i1 == idx {
             Some(i2)
         } else if i2 == idx {
-            return Some(i1);
+            Some(i1)
         } else {
             None
         }
@@ -157,3 +165,39 @@ impl Display for Edge {
         write!(f, "({}, {}, c={})", self.n1, self.n2, self.cost)
     }
 }
+
+#[cfg(test)]
+mod test_edge {
+    use super::*;
+
+    fn nodes(ids: &[u32]) -> Vec<Node> {
+        ids.iter().map(|i| Node::n(*i)).collect()
+    }
+
+    #[test]
+    fn test_endpoint_in() {
+        let edge = Edge::from_tuple(Node::n(0), Node::n(1));
+        assert_eq!(edge.endpoint_in(&nodes(&[0, 2])), Some(Node::n(0)));
+        assert_eq!(edge.endpoint_in(&nodes(&[1, 2])), Some(Node::n(1)));
+        assert_eq!(edge.endpoint_in(&nodes(&[1, 0])), Some(Node::n(0)));
+        assert_eq!(edge.endpoint_in(&nodes(&[2, 3])), None);
+        assert_eq!(edge.endpoint_in(&[]), None);
+    }
+
+    #[test]
+    fn test_both_endpoints_in() {
+        let edge = Edge::from_tuple(Node::n(0), Node::n(1));
+        assert!(edge.both_endpoints_in(&nodes(&[1, 2, 0])));
+        assert!(!edge.both_endpoints_in(&nodes(&[0, 2])));
+        assert!(!edge.both_endpoints_in(&nodes(&[2, 3])));
+    }
+
+    #[test]
+    fn test_one_sided_nodes_incident() {
+        let edge = Edge::from_tuple(Node::n(0), Node::n(1));
+        assert!(edge.one_sided_nodes_incident(&nodes(&[0, 2])));
+        assert!(edge.one_sided_nodes_incident(&nodes(&[1, 2])));
+        assert!(!edge.one_sided_nodes_incident(&nodes(&[0, 1])));
+        assert!(!edge.one_sided_nodes_incident(&nodes(&[2, 3])));
+    }
+}
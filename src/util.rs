@@ -15,7 +15,6 @@ pub fn hamiltonian_paths(v1: Node, v2: Node, nodes: &[Node]) -> Vec<Vec<Node>> {
         .collect_vec()
 }
 
-#[allow(dead_code)]
 pub fn get_local_merge_graph(
     comp1: &Component,
     comp2: &Component,
@@ -131,7 +130,7 @@ pub fn product_of_first<T: Clone + Copy + 'static>(
 }
 
 pub fn relabels_nodes_sequentially(comps: &mut [Component], mut offset: u32) {
-    for comp in comps {
+    for comp in comps.iter_mut() {
         match comp {
             Component::C7(nodes) => offset += relabel_slice(nodes, offset),
             Component::C6(nodes) => offset += relabel_slice(nodes, offset),
@@ -161,9 +160,50 @@ pub fn relabels_nodes_sequentially(comps: &mut [Component], mut offset: u32) {
               // }
         }
     }
+    assert_unique_node_ids(comps);
+}
+
+/// Panics if two of the given components share a node.
+pub fn assert_unique_node_ids(comps: &[Component]) {
+    assert!(
+        comps.iter().flat_map(|comp| comp.nodes()).all_unique(),
+        "Components {} have overlapping node ids",
+        comps.iter().join(", ")
+    );
 }
 
 fn relabel_slice(slice: &mut [Node], offset: u32) -> u32 {
     slice.iter_mut().for_each(|n| n.inc_id(offset));
     slice.len() as u32
 }
+
+#[cfg(test)]
+mod test_util {
+    use super::*;
+    use crate::comps::{c4, c5, large};
+
+    #[test]
+    fn test_assert_unique_node_ids() {
+        let mut comps = vec![c4(), c5()];
+        relabels_nodes_sequentially(&mut comps, 0);
+        assert_unique_node_ids(&comps);
+
+        // Node::Node(0) and Node::Comp(0) are different nodes
+        assert_unique_node_ids(&[c4(), large()]);
+    }
+
+    #[test]
+    fn test_relabel_large_comps() {
+        // every large component is represented by a single node
+        let mut comps = vec![large(), large(), large()];
+        relabels_nodes_sequentially(&mut comps, 0);
+        let nodes = comps.iter().flat_map(|c| c.nodes().to_vec()).collect_vec();
+        assert_eq!(nodes, Node::comp_range(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_unique_node_ids_overlap() {
+        assert_unique_node_ids(&[c4(), c5()]);
+    }
+}